@@ -0,0 +1,193 @@
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+// 解压总大小和文件数上限，防止压缩炸弹
+const MAX_EXTRACTED_BYTES: u64 = 256 * 1024 * 1024;
+const MAX_ENTRIES: usize = 10_000;
+
+// 打包工具自动生成的无关条目，判断单一顶层目录时忽略
+const IGNORED_ROOT_ENTRIES: &[&str] = &["__MACOSX", ".DS_Store"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+// foo.zip / foo.tar.gz / foo.tgz -> foo
+pub fn strip_archive_extension(name: &str) -> &str {
+    let lower = name.to_lowercase();
+    for ext in [".tar.gz", ".tgz", ".zip"] {
+        if lower.ends_with(ext) {
+            return &name[..name.len() - ext.len()];
+        }
+    }
+    name
+}
+
+// 条目路径只允许普通的相对路径：拒绝绝对路径、盘符和 ..（zip-slip）
+fn safe_entry_path(name: &str) -> Result<Option<PathBuf>, String> {
+    let normalized = name.replace('\\', "/");
+    if normalized.starts_with('/') || normalized.as_bytes().get(1) == Some(&b':') {
+        return Err(format!("Archive entry has an absolute path: {}", name));
+    }
+
+    let mut path = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return Err(format!("Archive entry escapes the target directory: {}", name)),
+        }
+    }
+
+    Ok(if path.as_os_str().is_empty() { None } else { Some(path) })
+}
+
+struct Extractor<'a> {
+    dest: &'a Path,
+    entries: usize,
+    written: u64,
+}
+
+impl Extractor<'_> {
+    fn check_entry_count(&mut self) -> Result<(), String> {
+        self.entries += 1;
+        if self.entries > MAX_ENTRIES {
+            return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+        }
+        Ok(())
+    }
+
+    fn create_dir(&self, rel: &Path) -> Result<(), String> {
+        fs::create_dir_all(self.dest.join(rel)).map_err(|e| e.to_string())
+    }
+
+    fn write_file(&mut self, rel: &Path, reader: &mut dyn Read) -> Result<(), String> {
+        let target = self.dest.join(rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut file = File::create(&target).map_err(|e| format!("Failed to create {}: {}", rel.display(), e))?;
+
+        // 多读一个字节用来判断是否超出上限
+        let remaining = MAX_EXTRACTED_BYTES - self.written;
+        let copied = io::copy(&mut reader.take(remaining + 1), &mut file)
+            .map_err(|e| format!("Failed to extract {}: {}", rel.display(), e))?;
+        if copied > remaining {
+            return Err(format!("Archive expands to more than {} MB", MAX_EXTRACTED_BYTES / 1024 / 1024));
+        }
+        self.written += copied;
+        Ok(())
+    }
+}
+
+fn extract_zip(archive: &Path, extractor: &mut Extractor) -> Result<(), String> {
+    let file = File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip archive: {}", e))?;
+
+    for i in 0..zip.len() {
+        extractor.check_entry_count()?;
+        let mut entry = zip.by_index(i).map_err(|e| format!("Invalid zip entry: {}", e))?;
+        let name = entry.name().to_string();
+        let rel = match safe_entry_path(&name)? {
+            Some(rel) => rel,
+            None => continue,
+        };
+
+        // 只接受普通文件和目录，符号链接和设备文件一律拒绝
+        if let Some(mode) = entry.unix_mode() {
+            let file_type = mode & 0o170000;
+            if file_type != 0 && file_type != 0o100000 && file_type != 0o040000 {
+                return Err(format!("Archive entry is not a regular file: {}", name));
+            }
+        }
+
+        if entry.is_dir() {
+            extractor.create_dir(&rel)?;
+        } else {
+            extractor.write_file(&rel, &mut entry)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_tar_gz(archive: &Path, extractor: &mut Extractor) -> Result<(), String> {
+    let file = File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let entries = tar.entries().map_err(|e| format!("Invalid tar archive: {}", e))?;
+
+    for entry in entries {
+        extractor.check_entry_count()?;
+        let mut entry = entry.map_err(|e| format!("Invalid tar entry: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("Invalid tar entry path: {}", e))?
+            .to_string_lossy()
+            .to_string();
+
+        match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                if let Some(rel) = safe_entry_path(&name)? {
+                    extractor.write_file(&rel, &mut entry)?;
+                }
+            }
+            tar::EntryType::Directory => {
+                if let Some(rel) = safe_entry_path(&name)? {
+                    extractor.create_dir(&rel)?;
+                }
+            }
+            // pax 扩展头只携带属性，不产生文件
+            tar::EntryType::XHeader | tar::EntryType::XGlobalHeader => {}
+            _ => return Err(format!("Archive entry is not a regular file: {}", name)),
+        }
+    }
+
+    Ok(())
+}
+
+// 安全解压到 dest，任何不安全的条目都会让整个解压失败
+pub fn extract_archive(archive: &Path, kind: ArchiveKind, dest: &Path) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+    let mut extractor = Extractor { dest, entries: 0, written: 0 };
+
+    match kind {
+        ArchiveKind::Zip => extract_zip(archive, &mut extractor),
+        ArchiveKind::TarGz => extract_tar_gz(archive, &mut extractor),
+    }
+}
+
+// 压缩包把所有内容包在唯一的顶层目录里时，返回该目录作为 skill 根目录
+pub fn unwrap_single_root(dir: &Path) -> PathBuf {
+    if dir.join("SKILL.md").is_file() {
+        return dir.to_path_buf();
+    }
+
+    let entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|e| !IGNORED_ROOT_ENTRIES.contains(&e.file_name().to_string_lossy().as_ref()))
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => return dir.to_path_buf(),
+    };
+
+    match entries.as_slice() {
+        [only] if only.is_dir() => only.clone(),
+        _ => dir.to_path_buf(),
+    }
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+use crate::{failed_import, git, install, source_url, ImportResult};
+
+// 发现结果保留 30 分钟，超时的克隆在下一次发现时清理
+const SESSION_TTL_MS: u64 = 30 * 60 * 1000;
+// 查找 SKILL.md 的最大目录深度
+const MAX_DISCOVERY_DEPTH: usize = 6;
+
+// 一次发现对应的克隆，安装时直接复用，不再重复克隆
+struct DiscoverySession {
+    repo_url: String,
+    repo_name: String,
+    clone_dir: PathBuf,
+    git_ref: Option<git::GitRef>,
+    commit_hash: String,
+    auth: git::GitAuth,
+    created_at: u64,
+}
+
+impl Drop for DiscoverySession {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.clone_dir);
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SESSIONS: Mutex<HashMap<String, DiscoverySession>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiscoverSkillsRequest {
+    #[serde(rename = "repoUrl")]
+    pub repo_url: String,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    pub auth: Option<git::GitAuth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiscoveredSkill {
+    // 仓库内的相对路径，仓库根目录本身就是 skill 时为空字符串
+    pub subpath: String,
+    // 安装目录名
+    #[serde(rename = "skillName")]
+    pub skill_name: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoDiscovery {
+    #[serde(rename = "discoveryId")]
+    pub discovery_id: String,
+    #[serde(rename = "repoUrl")]
+    pub repo_url: String,
+    #[serde(rename = "commitHash")]
+    pub commit_hash: String,
+    pub skills: Vec<DiscoveredSkill>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstallDiscoveredRequest {
+    #[serde(rename = "discoveryId")]
+    pub discovery_id: String,
+    pub subpaths: Vec<String>,
+    #[serde(rename = "installPath")]
+    pub install_path: Option<String>,
+    #[serde(rename = "skipSecurityCheck")]
+    pub skip_security_check: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiscoveredInstallResult {
+    pub subpath: String,
+    #[serde(flatten)]
+    pub result: ImportResult,
+}
+
+fn prune_expired_sessions() {
+    let now = crate::current_timestamp();
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        sessions.retain(|_, s| now.saturating_sub(s.created_at) < SESSION_TTL_MS);
+    }
+}
+
+fn dir_name_for(subpath: &str, repo_name: &str) -> String {
+    subpath
+        .rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(repo_name)
+        .to_string()
+}
+
+// 在 search_root 下查找所有包含 SKILL.md 的目录，跳过 .git 等隐藏目录
+fn find_skills(clone_dir: &Path, search_root: &Path, repo_name: &str) -> Vec<DiscoveredSkill> {
+    let mut skills = Vec::new();
+
+    for entry in WalkDir::new(search_root)
+        .max_depth(MAX_DISCOVERY_DEPTH)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() || entry.file_name() != "SKILL.md" {
+            continue;
+        }
+        let dir = match entry.path().parent() {
+            Some(dir) => dir,
+            None => continue,
+        };
+        let subpath = match dir.strip_prefix(clone_dir) {
+            Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+
+        let content = fs::read_to_string(entry.path()).unwrap_or_default();
+        let (description, name, _) = crate::parse_yaml_frontmatter(&content);
+        let skill_name = dir_name_for(&subpath, repo_name);
+
+        skills.push(DiscoveredSkill {
+            name: name.unwrap_or_else(|| skill_name.clone()),
+            subpath,
+            skill_name,
+            description,
+        });
+    }
+
+    skills.sort_by(|a, b| a.subpath.cmp(&b.subpath));
+    skills
+}
+
+fn discover(request: DiscoverSkillsRequest) -> Result<RepoDiscovery, String> {
+    prune_expired_sessions();
+
+    let source = source_url::parse_source_url(&request.repo_url)?;
+    let auth = crate::resolve_git_auth(request.auth);
+    let ref_name = request.git_ref.or_else(|| source.git_ref.clone());
+    let git_ref = match ref_name.as_deref().filter(|r| !r.is_empty()) {
+        Some(name) => Some(git::resolve_ref(&source.clone_url, name, &auth)?),
+        None => None,
+    };
+
+    let discovery_id = install::unique_suffix();
+    let clone_dir = std::env::temp_dir().join(format!("skill-manager-discover-{}", discovery_id));
+    let commit_hash = match git::clone_at_ref(&source.clone_url, &clone_dir, git_ref.as_ref(), None, &auth, None) {
+        Ok(commit) => commit,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&clone_dir);
+            return Err(format!("Git clone failed: {}", e));
+        }
+    };
+
+    let session = DiscoverySession {
+        repo_url: source.clone_url.clone(),
+        repo_name: source.repo_name.clone(),
+        clone_dir,
+        git_ref,
+        commit_hash: commit_hash.clone(),
+        auth,
+        created_at: crate::current_timestamp(),
+    };
+
+    // URL 指向子目录时只在该目录下查找
+    let search_root = match &source.subpath {
+        Some(sub) => session.clone_dir.join(sub),
+        None => session.clone_dir.clone(),
+    };
+    let skills = find_skills(&session.clone_dir, &search_root, &source.repo_name);
+
+    SESSIONS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(discovery_id.clone(), session);
+
+    Ok(RepoDiscovery {
+        discovery_id,
+        repo_url: source.clone_url,
+        commit_hash,
+        skills,
+    })
+}
+
+fn install_one(session: &DiscoverySession, install_dir: &Path, subpath: &str, skip_security_check: bool) -> ImportResult {
+    let sub = subpath.trim_matches('/');
+    if sub.split('/').any(|part| part == ".." || part == ".") {
+        return failed_import(format!("Invalid subpath: {}", subpath));
+    }
+
+    let skill_name = dir_name_for(sub, &session.repo_name);
+    let source_dir = if sub.is_empty() {
+        session.clone_dir.clone()
+    } else {
+        session.clone_dir.join(sub)
+    };
+
+    let staging = match install::StagingDir::new(install_dir, &skill_name) {
+        Ok(staging) => staging,
+        Err(e) => return failed_import(e),
+    };
+    let staged_skill = staging.path().join(&skill_name);
+
+    if let Err(e) = crate::copy_dir_all(&source_dir, &staged_skill) {
+        return failed_import(format!("Failed to copy skill: {}", e));
+    }
+
+    // 每个 skill 单独记录子路径和树哈希，检查更新时互不影响
+    let (subpath, tree_hash) = if sub.is_empty() {
+        (None, None)
+    } else {
+        let tree = git::run_git(Some(&session.clone_dir), &["rev-parse", &format!("HEAD:{}", sub)]).ok();
+        (Some(sub.to_string()), tree)
+    };
+    let metadata = crate::git_skill_metadata(
+        &session.repo_url,
+        session.git_ref.as_ref(),
+        session.commit_hash.clone(),
+        tree_hash,
+        subpath,
+        &session.auth,
+    );
+
+    install::finalize_install(&staged_skill, &install_dir.join(&skill_name), &skill_name, skip_security_check, &metadata, None)
+}
+
+fn install_discovered(request: InstallDiscoveredRequest) -> Result<Vec<DiscoveredInstallResult>, String> {
+    // 取出会话，安装结束后克隆随之删除
+    let session = SESSIONS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&request.discovery_id)
+        .ok_or("Discovery not found or expired, please scan the repository again")?;
+
+    let install_dir = match &request.install_path {
+        Some(path) => PathBuf::from(path).join(".claude").join("skills"),
+        None => crate::get_claude_skills_dir().ok_or("Cannot determine skills directory")?,
+    };
+    fs::create_dir_all(&install_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let skip = request.skip_security_check.unwrap_or(false);
+    Ok(request
+        .subpaths
+        .iter()
+        .map(|subpath| DiscoveredInstallResult {
+            subpath: subpath.clone(),
+            result: install_one(&session, &install_dir, subpath, skip),
+        })
+        .collect())
+}
+
+#[tauri::command(async)]
+pub async fn discover_repo_skills(request: DiscoverSkillsRequest) -> Result<RepoDiscovery, String> {
+    tokio::task::spawn_blocking(move || discover(request))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(async)]
+pub async fn install_discovered_skills(
+    request: InstallDiscoveredRequest,
+) -> Result<Vec<DiscoveredInstallResult>, String> {
+    tokio::task::spawn_blocking(move || install_discovered(request))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 用户放弃安装时释放克隆
+#[tauri::command]
+pub fn release_discovery(discovery_id: String) -> Result<(), String> {
+    SESSIONS.lock().map_err(|e| e.to_string())?.remove(&discovery_id);
+    Ok(())
+}
@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::progress::{self, InstallStage, InstallTask};
+
+// 访问私有仓库的认证方式，只记录密钥路径或环境变量名，不保存凭据本身
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum GitAuth {
+    // 使用系统 git 配置的凭据助手和 ssh-agent
+    #[default]
+    CredentialHelper,
+    SshKey {
+        #[serde(rename = "keyPath")]
+        key_path: String,
+    },
+    // 令牌从环境变量读取，通过临时凭据助手提供给 git，不会出现在命令行或 .git/config 中
+    TokenEnv {
+        #[serde(rename = "envVar")]
+        env_var: String,
+        username: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitErrorKind {
+    Auth,
+    Cancelled,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitError {
+    pub kind: GitErrorKind,
+    pub message: String,
+}
+
+impl GitError {
+    fn auth(message: String) -> Self {
+        Self { kind: GitErrorKind::Auth, message }
+    }
+
+    fn other(message: String) -> Self {
+        Self { kind: GitErrorKind::Other, message }
+    }
+
+    fn cancelled() -> Self {
+        Self { kind: GitErrorKind::Cancelled, message: "Installation cancelled".to_string() }
+    }
+
+    // 按 stderr 内容区分认证失败和其他错误
+    fn from_stderr(command: &str, stderr: &str) -> Self {
+        if is_auth_failure(stderr) {
+            return Self::auth(format!(
+                "Authentication required (git {}): {}. Configure a credential helper, an SSH key or a token environment variable for this repository",
+                command, stderr
+            ));
+        }
+        Self::other(format!("git {} failed: {}", command, stderr))
+    }
+
+    pub fn is_auth(&self) -> bool {
+        self.kind == GitErrorKind::Auth
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.kind == GitErrorKind::Cancelled
+    }
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<GitError> for String {
+    fn from(e: GitError) -> String {
+        e.message
+    }
+}
+
+// git 在认证失败时的典型输出
+const AUTH_FAILURE_PATTERNS: &[&str] = &[
+    "authentication failed",
+    "could not read username",
+    "could not read password",
+    "terminal prompts disabled",
+    "permission denied (publickey",
+    "host key verification failed",
+    "invalid username or password",
+    "http basic: access denied",
+    "repository not found",
+    "the requested url returned error: 401",
+    "the requested url returned error: 403",
+];
+
+fn is_auth_failure(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    AUTH_FAILURE_PATTERNS.iter().any(|p| stderr.contains(p))
+}
+
+fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// 禁止一切交互式提示，缺少凭据时立即失败而不是挂起后台任务
+fn apply_auth(cmd: &mut Command, auth: &GitAuth) -> Result<(), GitError> {
+    cmd.env("GIT_TERMINAL_PROMPT", "0").env("GCM_INTERACTIVE", "never");
+
+    match auth {
+        GitAuth::CredentialHelper => {
+            if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+                cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+            }
+        }
+        GitAuth::SshKey { key_path } => {
+            let key = crate::expand_home(key_path).map_err(GitError::auth)?;
+            if !key.is_file() {
+                return Err(GitError::auth(format!("SSH key not found: {}", key.display())));
+            }
+            cmd.env(
+                "GIT_SSH_COMMAND",
+                format!(
+                    "ssh -i {} -o IdentitiesOnly=yes -o BatchMode=yes",
+                    shell_quote(&key.to_string_lossy().replace('\\', "/"))
+                ),
+            );
+        }
+        GitAuth::TokenEnv { env_var, username } => {
+            if !is_valid_env_name(env_var) {
+                return Err(GitError::auth(format!("Invalid environment variable name: {}", env_var)));
+            }
+            if std::env::var(env_var).map(|v| v.is_empty()).unwrap_or(true) {
+                return Err(GitError::auth(format!("Environment variable {} is not set", env_var)));
+            }
+            let username = username.as_deref().unwrap_or("x-access-token");
+            let valid = !username.is_empty()
+                && username.chars().all(|c| c.is_ascii_alphanumeric() || "-_.@".contains(c));
+            if !valid {
+                return Err(GitError::auth(format!("Invalid username for token auth: {}", username)));
+            }
+            // 先清空已配置的凭据助手，再注册只读取该环境变量的助手
+            let helper = format!(
+                "!f() {{ test \"$1\" = get || exit 0; echo username={}; echo \"password=${{{}}}\"; }}; f",
+                username, env_var
+            );
+            cmd.env("GIT_CONFIG_COUNT", "2")
+                .env("GIT_CONFIG_KEY_0", "credential.helper")
+                .env("GIT_CONFIG_VALUE_0", "")
+                .env("GIT_CONFIG_KEY_1", "credential.helper")
+                .env("GIT_CONFIG_VALUE_1", helper);
+        }
+    }
+
+    Ok(())
+}
+
+// 执行 git 命令，成功时返回去除首尾空白的 stdout
+pub fn run_git(dir: Option<&Path>, args: &[&str]) -> Result<String, GitError> {
+    run_git_auth(dir, args, &GitAuth::default())
+}
+
+// 需要访问远程仓库的命令使用指定的认证方式
+pub fn run_git_auth(dir: Option<&Path>, args: &[&str], auth: &GitAuth) -> Result<String, GitError> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    apply_auth(&mut cmd, auth)?;
+
+    let output = cmd
+        .args(args)
+        .output()
+        .map_err(|e| GitError::other(format!("Git command failed: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::from_stderr(args.first().unwrap_or(&""), stderr.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// 将 git --progress 的输出换算为整体进度：接收对象占 90%，解析增量占 10%
+fn clone_percent(line: &str) -> Option<u32> {
+    let line = line.trim_start_matches("remote: ");
+    let (phase, rest) = line.split_once(':')?;
+    let digits: String = rest.trim_start().chars().take_while(|c| c.is_ascii_digit()).collect();
+    if !rest.trim_start()[digits.len()..].starts_with('%') {
+        return None;
+    }
+    let percent: u32 = digits.parse().ok()?;
+    match phase {
+        "Receiving objects" => Some(percent * 90 / 100),
+        "Resolving deltas" => Some(90 + percent / 10),
+        _ => None,
+    }
+}
+
+// 长时间运行的克隆：逐行读取进度并上报，登记子进程以便取消
+fn run_git_tracked(args: &[&str], auth: &GitAuth, task: &InstallTask) -> Result<(), GitError> {
+    if task.is_cancelled() {
+        return Err(GitError::cancelled());
+    }
+
+    let mut cmd = Command::new("git");
+    apply_auth(&mut cmd, auth)?;
+    progress::isolate_process_group(&mut cmd);
+    let mut child = cmd
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitError::other(format!("Git command failed: {}", e)))?;
+    task.set_child(Some(child.id()));
+
+    // 进度行以 \r 刷新，其余输出保留用于错误信息
+    let mut messages: Vec<String> = Vec::new();
+    let mut last_percent = None;
+    let mut handle_line = |line: &[u8]| {
+        let line = String::from_utf8_lossy(line).trim().to_string();
+        if line.is_empty() {
+            return;
+        }
+        match clone_percent(&line) {
+            Some(percent) => {
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    task.emit(InstallStage::Cloning, Some(percent), None);
+                }
+            }
+            None if !line.contains('%') => messages.push(line),
+            None => {}
+        }
+    };
+
+    if let Some(mut stderr) = child.stderr.take() {
+        let mut buf = [0u8; 4096];
+        let mut line = Vec::new();
+        while let Ok(n) = stderr.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            for &b in &buf[..n] {
+                if b == b'\r' || b == b'\n' {
+                    handle_line(&line);
+                    line.clear();
+                } else {
+                    line.push(b);
+                }
+            }
+        }
+        handle_line(&line);
+    }
+
+    let status = child.wait().map_err(|e| GitError::other(format!("Git command failed: {}", e)))?;
+    task.set_child(None);
+
+    if task.is_cancelled() {
+        return Err(GitError::cancelled());
+    }
+    if !status.success() {
+        return Err(GitError::from_stderr(args.first().unwrap_or(&""), &messages.join("\n")));
+    }
+    Ok(())
+}
+
+// 通过 git ls-remote 获取远程引用指向的 commit，branch 为空时使用 HEAD
+pub fn ls_remote(repo_url: &str, branch: Option<&str>, auth: &GitAuth) -> Result<String, GitError> {
+    let pattern = match branch {
+        Some(b) => format!("refs/heads/{}", b),
+        None => "HEAD".to_string(),
+    };
+
+    let output = run_git_auth(None, &["ls-remote", repo_url, &pattern], auth)?;
+
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .next()
+        .map(|hash| hash.to_string())
+        .ok_or_else(|| GitError::other(format!("Ref {} not found in {}", pattern, repo_url)))
+}
+
+// 固定安装的引用类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitRef {
+    Branch(String),
+    Tag(String),
+    Commit(String),
+}
+
+impl GitRef {
+    pub fn name(&self) -> &str {
+        match self {
+            GitRef::Branch(n) | GitRef::Tag(n) | GitRef::Commit(n) => n,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GitRef::Branch(_) => "branch",
+            GitRef::Tag(_) => "tag",
+            GitRef::Commit(_) => "commit",
+        }
+    }
+
+    pub fn from_parts(kind: &str, name: &str) -> Option<GitRef> {
+        match kind {
+            "branch" => Some(GitRef::Branch(name.to_string())),
+            "tag" => Some(GitRef::Tag(name.to_string())),
+            "commit" => Some(GitRef::Commit(name.to_string())),
+            _ => None,
+        }
+    }
+}
+
+fn looks_like_sha(name: &str) -> bool {
+    (4..=40).contains(&name.len()) && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// 判断引用是分支、标签还是 commit，优先匹配远程存在的分支和标签
+pub fn resolve_ref(repo_url: &str, name: &str, auth: &GitAuth) -> Result<GitRef, GitError> {
+    let heads = format!("refs/heads/{}", name);
+    let tags = format!("refs/tags/{}", name);
+    let output = run_git_auth(None, &["ls-remote", repo_url, &heads, &tags], auth)?;
+
+    let refs: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+
+    if refs.contains(&heads.as_str()) {
+        Ok(GitRef::Branch(name.to_string()))
+    } else if refs.iter().any(|r| r.trim_end_matches("^{}") == tags) {
+        Ok(GitRef::Tag(name.to_string()))
+    } else if looks_like_sha(name) {
+        Ok(GitRef::Commit(name.to_string()))
+    } else {
+        Err(GitError::other(format!("Ref {} not found in {}", name, repo_url)))
+    }
+}
+
+// 按引用克隆到 dest，sparse_path 非空时只检出该子目录，返回实际检出的 commit
+// 每一步都检查退出状态，检出失败不会被静默忽略
+pub fn clone_at_ref(
+    repo_url: &str,
+    dest: &Path,
+    git_ref: Option<&GitRef>,
+    sparse_path: Option<&str>,
+    auth: &GitAuth,
+    task: Option<&InstallTask>,
+) -> Result<String, GitError> {
+    let dest_str = dest.to_string_lossy().to_string();
+    // 需要上报进度时强制输出 --progress（stderr 不是终端时 git 默认不输出）
+    let mut args: Vec<&str> = vec!["clone", if task.is_some() { "--progress" } else { "--quiet" }];
+
+    match git_ref {
+        // 任意 commit 无法浅克隆，使用不含文件内容的完整历史再检出
+        Some(GitRef::Commit(_)) => args.extend(["--filter=blob:none", "--no-checkout"]),
+        Some(GitRef::Branch(name)) | Some(GitRef::Tag(name)) => {
+            args.extend(["--depth", "1", "--branch", name.as_str()])
+        }
+        None => args.extend(["--depth", "1"]),
+    }
+    if sparse_path.is_some() {
+        if !matches!(git_ref, Some(GitRef::Commit(_))) {
+            args.push("--filter=blob:none");
+        }
+        args.push("--sparse");
+    }
+    args.push(repo_url);
+    args.push(&dest_str);
+
+    match task {
+        Some(task) => run_git_tracked(&args, auth, task)?,
+        None => {
+            run_git_auth(None, &args, auth)?;
+        }
+    }
+    if task.map(|t| t.is_cancelled()).unwrap_or(false) {
+        return Err(GitError::cancelled());
+    }
+
+    // blob:none 克隆在检出时按需下载文件内容，同样需要认证
+    if let Some(path) = sparse_path {
+        run_git_auth(Some(dest), &["sparse-checkout", "set", path], auth)?;
+    }
+
+    if let Some(GitRef::Commit(sha)) = git_ref {
+        run_git_auth(Some(dest), &["checkout", "--quiet", "--detach", sha], auth)?;
+    }
+
+    run_git(Some(dest), &["rev-parse", "HEAD"])
+}
+
+// 列出远程所有标签及其指向的 commit（附注标签取解引用后的 commit）
+pub fn list_remote_tags(repo_url: &str, auth: &GitAuth) -> Result<Vec<(String, String)>, GitError> {
+    let output = run_git_auth(None, &["ls-remote", "--tags", repo_url], auth)?;
+    let mut tags: Vec<(String, String)> = Vec::new();
+
+    for line in output.lines() {
+        let mut cols = line.split_whitespace();
+        let (hash, name) = match (cols.next(), cols.next()) {
+            (Some(h), Some(n)) => (h, n),
+            _ => continue,
+        };
+        let name = match name.strip_prefix("refs/tags/") {
+            Some(n) => n,
+            None => continue,
+        };
+        match name.strip_suffix("^{}") {
+            Some(peeled) => {
+                if let Some(entry) = tags.iter_mut().find(|(n, _)| n == peeled) {
+                    entry.1 = hash.to_string();
+                } else {
+                    tags.push((peeled.to_string(), hash.to_string()));
+                }
+            }
+            None => {
+                if !tags.iter().any(|(n, _)| n == name) {
+                    tags.push((name.to_string(), hash.to_string()));
+                }
+            }
+        }
+    }
+
+    Ok(tags)
+}
+
+// 将 v1.2.3 / 1.2 之类的标签解析为数字序列，非版本号标签返回 None
+pub fn parse_version_tag(tag: &str) -> Option<Vec<u64>> {
+    let version = tag.trim_start_matches(['v', 'V']);
+    let version = version.split(['-', '+']).next()?;
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod scratch {
+    use super::*;
+    #[test]
+    fn percent() {
+        for l in ["Receiving objects:  45% (9/20)", "Resolving deltas: 100% (3/3), done.", "remote: Counting objects: 100% (5/5)", "Cloning into 'x'..."] {
+            println!("{:?} -> {:?}", l, clone_percent(l));
+        }
+    }
+    #[test]
+    fn cancel() {
+        let t = tempfile::tempdir().unwrap();
+        let w = t.path().join("w");
+        std::fs::create_dir_all(&w).unwrap();
+        run_git(Some(&w), &["init", "-q"]).unwrap();
+        for i in 0..3000 { std::fs::write(w.join(format!("f{}", i)), format!("{}{}", i, "x".repeat(20000 + i))).unwrap(); }
+        run_git(Some(&w), &["add", "-A"]).unwrap();
+        run_git(Some(&w), &["-c", "user.name=a", "-c", "user.email=a@a", "commit", "-qm", "x"]).unwrap();
+        let app = tauri::AppHandle;
+        let task = progress::register(&app, Some("t1".into())).unwrap();
+        let t2 = task.clone();
+        let url = format!("file://{}", w.display());
+        let dest = t.path().join("dest");
+        let start = std::time::Instant::now();
+        let h = std::thread::spawn(move || clone_at_ref(&url, &dest, None, None, &GitAuth::default(), Some(&t2)));
+        std::thread::sleep(std::time::Duration::from_millis(150));
+        println!("cancel -> {:?}", progress::cancel_install("t1".into()));
+        let r = h.join().unwrap();
+        println!("result {:?} after {:?}", r.map_err(|e| (e.kind, e.message)), start.elapsed());
+        // full clone timing for comparison
+        let start = std::time::Instant::now();
+        clone_at_ref(&format!("file://{}", w.display()), &t.path().join("d2"), None, None, &GitAuth::default(), None).unwrap();
+        println!("full clone {:?}", start.elapsed());
+    }
+}
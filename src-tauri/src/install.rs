@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::progress::{InstallStage, InstallTask};
+use crate::{cancelled_import, failed_import, save_skill_metadata, security, versions, ImportResult, SkillMetadata};
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// 生成进程内唯一的后缀，避免并发安装共用同一个临时目录
+pub fn unique_suffix() -> String {
+    format!(
+        "{}-{}-{}",
+        std::process::id(),
+        crate::current_timestamp(),
+        STAGING_COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+// 暂存目录：建在安装目录下，保证与目标目录位于同一文件系统，rename 才是原子的
+// 离开作用域时自动删除，任何失败路径都不会留下半成品
+pub struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    pub fn new(install_dir: &Path, skill_name: &str) -> Result<Self, String> {
+        let path = install_dir.join(format!(".staging-{}-{}", skill_name, unique_suffix()));
+        fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// 校验暂存内容是一个有效的 skill
+pub fn validate_skill_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("Skill directory not found: {}", dir.display()));
+    }
+    if !dir.join("SKILL.md").is_file() {
+        return Err("SKILL.md not found in skill directory".to_string());
+    }
+    Ok(())
+}
+
+// 用暂存目录替换目标目录：旧版本先改名备份，新版本就位后才归档备份，失败时还原旧版本
+pub fn swap_into_place(staged: &Path, target: &Path) -> Result<(), String> {
+    let had_previous = fs::symlink_metadata(target).is_ok();
+    let backup = target.with_file_name(format!(
+        ".backup-{}-{}",
+        target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        unique_suffix()
+    ));
+
+    if had_previous {
+        fs::rename(target, &backup)
+            .map_err(|e| format!("Failed to back up existing skill: {}", e))?;
+    }
+
+    if let Err(e) = fs::rename(staged, target) {
+        if had_previous {
+            let _ = fs::rename(&backup, target);
+        }
+        return Err(format!("Failed to move skill into place: {}", e));
+    }
+
+    // 旧版本移入版本库以便回退，归档失败或旧路径只是软链接时直接删除
+    if had_previous {
+        let is_dir = fs::symlink_metadata(&backup).map(|m| m.is_dir()).unwrap_or(false);
+        if !is_dir || versions::archive_version(&backup, target).is_err() {
+            let _ = fs::remove_dir_all(&backup).or_else(|_| fs::remove_file(&backup));
+        }
+    }
+
+    Ok(())
+}
+
+// 安装的最后阶段：校验、安全扫描、写入元数据、原子替换
+pub fn finalize_install(
+    staged_skill: &Path,
+    target_dir: &Path,
+    skill_name: &str,
+    skip_security_check: bool,
+    metadata: &SkillMetadata,
+    task: Option<&InstallTask>,
+) -> ImportResult {
+    if let Err(e) = validate_skill_dir(staged_skill) {
+        return failed_import(e);
+    }
+
+    if let Some(task) = task {
+        task.emit(InstallStage::Scanning, None, None);
+    }
+
+    // 命中硬性规则时除非显式跳过否则中止安装
+    let report = match security::scan_directory(staged_skill, skill_name) {
+        Ok(report) => report,
+        Err(e) => return failed_import(format!("Security scan failed: {}", e)),
+    };
+
+    if report.blocked && !skip_security_check {
+        return ImportResult {
+            success: false,
+            message: format!("Installation of {} blocked by security scan (score {})", skill_name, report.score),
+            blocked: true,
+            security_report: Some(report),
+            error_kind: None,
+        };
+    }
+
+    if let Some(task) = task {
+        task.emit(InstallStage::WritingMetadata, None, None);
+    }
+    if let Err(e) = save_skill_metadata(&staged_skill.to_path_buf(), metadata) {
+        return failed_import(e);
+    }
+
+    // 替换前最后一次检查取消，替换开始后不再中断
+    if task.map(|t| t.is_cancelled()).unwrap_or(false) {
+        return cancelled_import();
+    }
+
+    if let Err(e) = swap_into_place(staged_skill, target_dir) {
+        return failed_import(e);
+    }
+
+    ImportResult {
+        success: true,
+        message: format!("Successfully installed {} to {}", skill_name, target_dir.display()),
+        blocked: false,
+        security_report: Some(report),
+        error_kind: None,
+    }
+}
@@ -1,10 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use walkdir::WalkDir;
 
+mod archive;
+mod discovery;
+mod git;
+mod install;
+mod progress;
 mod security;
+mod source_url;
+mod update;
+mod versions;
 use security::SecurityReport;
 
 // 主目录配置
@@ -176,10 +184,15 @@ pub struct SkillInfo {
     pub install_date: Option<u64>,
     #[serde(rename = "commitHash")]
     pub commit_hash: Option<String>,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    #[serde(rename = "refType")]
+    pub ref_type: Option<String>,
+    pub subpath: Option<String>,
 }
 
 // Skill 元数据 - 存储在每个 skill 目录的 .skill-meta.json
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SkillMetadata {
     pub source: String,  // "marketplace" | "github" | "local"
     #[serde(rename = "sourceUrl")]
@@ -188,6 +201,19 @@ pub struct SkillMetadata {
     pub install_date: u64,
     #[serde(rename = "commitHash")]
     pub commit_hash: Option<String>,
+    // /tree/ 安装时子路径对应的 git 树哈希，用于判断子目录是否有更新
+    #[serde(rename = "treeHash")]
+    pub tree_hash: Option<String>,
+    // 仓库内的 skill 子目录，同一仓库安装多个 skill 时各自记录
+    pub subpath: Option<String>,
+    // 安装时固定的引用（分支、标签或 commit），重装时据此复现相同内容
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    #[serde(rename = "refType")]
+    pub ref_type: Option<String>,  // "branch" | "tag" | "commit"
+    // 安装时使用的认证方式，检查更新时复用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<git::GitAuth>,
     pub version: Option<String>,
     pub author: Option<String>,
     pub description: Option<String>,
@@ -213,6 +239,16 @@ pub struct ImportGithubRequest {
     pub install_path: Option<String>,
     #[serde(rename = "skipSecurityCheck")]
     pub skip_security_check: bool,
+    // 要安装的标签、分支或 commit，默认使用 /tree/ URL 中的分支或仓库默认分支
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    // 仓库内要安装的子目录，优先于 URL 中的路径
+    pub subpath: Option<String>,
+    // 前端生成的安装 ID，用于订阅进度事件和取消
+    #[serde(rename = "installId")]
+    pub install_id: Option<String>,
+    // 私有仓库的认证方式，默认读取配置中的 gitAuth
+    pub auth: Option<git::GitAuth>,
     // 市场元数据（从市场安装时传入）
     #[serde(rename = "isMarketplace")]
     pub is_marketplace: Option<bool>,
@@ -230,6 +266,52 @@ pub struct ImportResult {
     pub success: bool,
     pub message: String,
     pub blocked: bool,
+    #[serde(rename = "securityReport", skip_serializing_if = "Option::is_none")]
+    pub security_report: Option<SecurityReport>,
+    // 失败类型："auth" 需要配置凭据，"cancelled" 为用户取消
+    #[serde(rename = "errorKind", skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+}
+
+fn failed_import(message: String) -> ImportResult {
+    ImportResult {
+        success: false,
+        message,
+        blocked: false,
+        security_report: None,
+        error_kind: None,
+    }
+}
+
+fn cancelled_import() -> ImportResult {
+    ImportResult {
+        error_kind: Some("cancelled".to_string()),
+        ..failed_import("Installation cancelled".to_string())
+    }
+}
+
+// git 认证失败时带上 errorKind，前端据此引导用户配置凭据
+fn git_failed_import(context: &str, e: git::GitError) -> ImportResult {
+    if e.is_cancelled() {
+        return cancelled_import();
+    }
+    let error_kind = if e.is_auth() { Some("auth".to_string()) } else { None };
+    ImportResult {
+        error_kind,
+        ..failed_import(format!("{}{}", context, e))
+    }
+}
+
+// 请求未指定认证方式时使用配置中的默认值
+fn resolve_git_auth(requested: Option<git::GitAuth>) -> git::GitAuth {
+    requested
+        .or_else(|| {
+            load_config()
+                .ok()
+                .and_then(|c| c.get("gitAuth").cloned())
+                .and_then(|v| serde_json::from_value(v).ok())
+        })
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Deserialize)]
@@ -246,6 +328,8 @@ pub struct ImportLocalRequest {
     pub install_path: Option<String>,
     #[serde(rename = "skillName")]
     pub skill_name: String,
+    #[serde(rename = "skipSecurityCheck")]
+    pub skip_security_check: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -457,9 +541,17 @@ fn parse_skill_md(path: &PathBuf, skill_type: &str) -> Option<SkillInfo> {
         source_url: metadata.as_ref().and_then(|m| m.source_url.clone()),
         install_date: metadata.as_ref().map(|m| m.install_date),
         commit_hash: metadata.as_ref().and_then(|m| m.commit_hash.clone()),
+        git_ref: metadata.as_ref().and_then(|m| m.git_ref.clone()),
+        ref_type: metadata.as_ref().and_then(|m| m.ref_type.clone()),
+        subpath: metadata.as_ref().and_then(|m| m.subpath.clone()),
     })
 }
 
+// 跳过隐藏目录（.staging-*、.backup-* 等安装过程中的临时目录），根目录除外
+fn is_hidden_entry(entry: &walkdir::DirEntry) -> bool {
+    entry.depth() > 0 && entry.file_name().to_string_lossy().starts_with('.')
+}
+
 #[tauri::command]
 fn scan_skills() -> Result<ScanResult, String> {
     let mut system_skills = Vec::new();
@@ -467,7 +559,7 @@ fn scan_skills() -> Result<ScanResult, String> {
 
     if let Some(skills_dir) = get_claude_skills_dir() {
         if skills_dir.exists() {
-            for entry in WalkDir::new(&skills_dir).max_depth(3) {
+            for entry in WalkDir::new(&skills_dir).max_depth(3).into_iter().filter_entry(|e| !is_hidden_entry(e)) {
                 if let Ok(entry) = entry {
                     let path = entry.path();
                     if path.file_name().map(|n| n == "SKILL.md").unwrap_or(false) {
@@ -484,7 +576,7 @@ fn scan_skills() -> Result<ScanResult, String> {
         for project_path in paths {
             let skills_dir = PathBuf::from(&project_path).join(".claude").join("skills");
             if skills_dir.exists() {
-                for entry in WalkDir::new(&skills_dir).max_depth(3) {
+                for entry in WalkDir::new(&skills_dir).max_depth(3).into_iter().filter_entry(|e| !is_hidden_entry(e)) {
                     if let Ok(entry) = entry {
                         let path = entry.path();
                         if path.file_name().map(|n| n == "SKILL.md").unwrap_or(false) {
@@ -504,22 +596,54 @@ fn scan_skills() -> Result<ScanResult, String> {
     })
 }
 
+// 从 git 仓库安装的元数据，version / author 会从 SKILL.md 中提取
+fn git_skill_metadata(
+    repo_url: &str,
+    git_ref: Option<&git::GitRef>,
+    commit_hash: String,
+    tree_hash: Option<String>,
+    subpath: Option<String>,
+    auth: &git::GitAuth,
+) -> SkillMetadata {
+    SkillMetadata {
+        source: "github".to_string(),
+        source_url: Some(repo_url.to_string()),
+        install_date: current_timestamp(),
+        // commit 固定记录完整哈希，避免短哈希日后产生歧义
+        git_ref: git_ref.map(|r| match r {
+            git::GitRef::Commit(_) => commit_hash.clone(),
+            _ => r.name().to_string(),
+        }),
+        commit_hash: Some(commit_hash),
+        tree_hash,
+        subpath,
+        ref_type: git_ref.map(|r| r.kind().to_string()),
+        auth: Some(auth.clone()).filter(|a| *a != git::GitAuth::default()),
+        ..Default::default()
+    }
+}
+
 #[tauri::command(async)]
-async fn import_github_skill(request: ImportGithubRequest) -> Result<ImportResult, String> {
+async fn import_github_skill(app: tauri::AppHandle, request: ImportGithubRequest) -> Result<ImportResult, String> {
     let repo_url = request.repo_url.clone();
 
+    // 传入 installId 时通过 install-progress:<installId> 事件上报进度，并可用 cancel_install 取消
+    let task = match progress::register(&app, request.install_id.clone()) {
+        Ok(task) => task,
+        Err(e) => return Ok(failed_import(e)),
+    };
+    let blocking_task = task.clone();
+
     let result = tokio::task::spawn_blocking(move || {
-        let parts: Vec<&str> = repo_url
-            .trim_end_matches('/')
-            .split('/')
-            .collect();
+        let task = blocking_task;
+        task.emit(progress::InstallStage::Resolving, None, None);
 
-        if parts.len() < 5 {
-            return ImportResult {
-                success: false,
-                message: "Invalid GitHub URL".to_string(),
-                blocked: false,
-            };
+        let mut source = match source_url::parse_source_url(&repo_url) {
+            Ok(source) => source,
+            Err(e) => return failed_import(e),
+        };
+        if let Some(sub) = request.subpath.as_deref().map(|s| s.trim_matches('/')).filter(|s| !s.is_empty()) {
+            source.subpath = Some(sub.to_string());
         }
 
         // 始终安装到 Claude Code 主目录
@@ -528,151 +652,62 @@ async fn import_github_skill(request: ImportGithubRequest) -> Result<ImportResul
         } else {
             match get_claude_skills_dir() {
                 Some(dir) => dir,
-                None => return ImportResult {
-                    success: false,
-                    message: "Cannot determine skills directory".to_string(),
-                    blocked: false,
-                },
+                None => return failed_import("Cannot determine skills directory".to_string()),
             }
         };
 
         if let Err(e) = fs::create_dir_all(&install_dir) {
-            return ImportResult {
-                success: false,
-                message: format!("Failed to create directory: {}", e),
-                blocked: false,
-            };
+            return failed_import(format!("Failed to create directory: {}", e));
         }
 
-        let skill_name = if repo_url.contains("/tree/") {
-            parts.last().unwrap_or(&"skill").to_string()
-        } else {
-            parts.get(4).unwrap_or(&"skill").to_string()
-        };
+        let skill_name = source.skill_name();
 
         let target_dir = install_dir.join(&skill_name);
 
-        if repo_url.contains("/tree/") {
-            let repo_base = format!("https://github.com/{}/{}", parts[3], parts[4]);
-            let branch = parts.get(6).unwrap_or(&"main");
-            let subpath = parts[7..].join("/");
-
-            let temp_dir = install_dir.join(".temp_clone");
-            let _ = fs::remove_dir_all(&temp_dir);
-
-            let output = Command::new("git")
-                .args(["clone", "--depth", "1", "--filter=blob:none", "--sparse", &repo_base, temp_dir.to_str().unwrap()])
-                .output();
-
-            match output {
-                Err(e) => return ImportResult {
-                    success: false,
-                    message: format!("Git command failed: {}", e),
-                    blocked: false,
-                },
-                Ok(o) if !o.status.success() => return ImportResult {
-                    success: false,
-                    message: format!("Git clone failed: {}", String::from_utf8_lossy(&o.stderr)),
-                    blocked: false,
-                },
-                _ => {}
-            }
-
-            let _ = Command::new("git")
-                .current_dir(&temp_dir)
-                .args(["sparse-checkout", "set", &subpath])
-                .output();
-
-            let _ = Command::new("git")
-                .current_dir(&temp_dir)
-                .args(["checkout", branch])
-                .output();
-
-            let source = temp_dir.join(&subpath);
-            if source.exists() {
-                let _ = fs::remove_dir_all(&target_dir);
-                if let Err(e) = fs::rename(&source, &target_dir) {
-                    let _ = fs::remove_dir_all(&temp_dir);
-                    return ImportResult {
-                        success: false,
-                        message: format!("Failed to move skill: {}", e),
-                        blocked: false,
-                    };
-                }
-
-                // 保存元数据 (sparse checkout)
-                let metadata = SkillMetadata {
-                    source: "github".to_string(),
-                    source_url: Some(repo_url.clone()),
-                    install_date: current_timestamp(),
-                    commit_hash: None,  // sparse checkout 不保留 git 信息
-                    version: None,
-                    author: None,
-                    description: None,
-                    description_zh: None,
-                    description_en: None,
-                };
-                let _ = save_skill_metadata(&target_dir, &metadata);
-            }
+        // 先克隆到独立的暂存目录，校验和扫描通过后再替换目标目录，旧版本在替换成功前一直保留
+        let staging = match install::StagingDir::new(&install_dir, &skill_name) {
+            Ok(staging) => staging,
+            Err(e) => return failed_import(e),
+        };
+        let clone_dir = staging.path().join("repo");
+
+        let repo_base = source.clone_url.clone();
+        let subpath = source.subpath.clone();
+
+        // 显式指定的引用优先，其次是 URL 中的分支（也可能是标签）
+        let ref_name = request.git_ref.clone().or_else(|| source.git_ref.clone());
+        let auth = resolve_git_auth(request.auth.clone());
+        let git_ref = match ref_name.as_deref().filter(|r| !r.is_empty()) {
+            Some(name) => match git::resolve_ref(&repo_base, name, &auth) {
+                Ok(r) => Some(r),
+                Err(e) => return git_failed_import("", e),
+            },
+            None => None,
+        };
 
-            let _ = fs::remove_dir_all(&temp_dir);
-        } else {
-            let _ = fs::remove_dir_all(&target_dir);
-
-            let output = Command::new("git")
-                .args(["clone", "--depth", "1", &repo_url, target_dir.to_str().unwrap()])
-                .output();
-
-            match output {
-                Err(e) => return ImportResult {
-                    success: false,
-                    message: format!("Git command failed: {}", e),
-                    blocked: false,
-                },
-                Ok(o) if !o.status.success() => return ImportResult {
-                    success: false,
-                    message: format!("Git clone failed: {}", String::from_utf8_lossy(&o.stderr)),
-                    blocked: false,
-                },
-                _ => {}
-            }
-        }
+        task.emit(progress::InstallStage::Cloning, Some(0), None);
+        let commit_hash = match git::clone_at_ref(&repo_base, &clone_dir, git_ref.as_ref(), subpath.as_deref(), &auth, Some(&task)) {
+            Ok(commit) => commit,
+            Err(e) => return git_failed_import("Git clone failed: ", e),
+        };
 
-        // 获取 commit hash
-        let commit_hash = Command::new("git")
-            .current_dir(&target_dir)
-            .args(["rev-parse", "HEAD"])
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                } else {
-                    None
-                }
-            });
-
-        // 保存元数据
-        let metadata = SkillMetadata {
-            source: "github".to_string(),
-            source_url: Some(repo_url.clone()),
-            install_date: current_timestamp(),
-            commit_hash,
-            version: None,  // 会从 SKILL.md 中提取
-            author: None,   // 会从 SKILL.md 中提取
-            description: None,
-            description_zh: None,
-            description_en: None,
+        // 记录子路径树哈希，sparse checkout 移动后不再保留 git 信息
+        let (source_dir, tree_hash) = match &subpath {
+            Some(sub) => (
+                clone_dir.join(sub),
+                git::run_git(Some(&clone_dir), &["rev-parse", &format!("HEAD:{}", sub)]).ok(),
+            ),
+            None => (clone_dir.clone(), None),
         };
-        let _ = save_skill_metadata(&target_dir, &metadata);
 
-        ImportResult {
-            success: true,
-            message: format!("Successfully installed {} to {}", skill_name, target_dir.display()),
-            blocked: false,
-        }
-    }).await.map_err(|e| e.to_string())?;
+        let metadata = git_skill_metadata(&repo_url, git_ref.as_ref(), commit_hash, tree_hash, subpath, &auth);
+
+        install::finalize_install(&source_dir, &target_dir, &skill_name, request.skip_security_check, &metadata, Some(&task))
+    }).await;
 
+    progress::unregister(&task);
+    let result = result.map_err(|e| e.to_string())?;
+    task.finish(&result);
     Ok(result)
 }
 
@@ -685,6 +720,8 @@ fn uninstall_skill(request: UninstallRequest) -> Result<ImportResult, String> {
             success: false,
             message: "Skill path is empty".to_string(),
             blocked: false,
+            security_report: None,
+            error_kind: None,
         });
     }
 
@@ -695,6 +732,8 @@ fn uninstall_skill(request: UninstallRequest) -> Result<ImportResult, String> {
             success: false,
             message: format!("Skill path does not exist: {}", skill_path),
             blocked: false,
+            security_report: None,
+            error_kind: None,
         });
     }
 
@@ -704,6 +743,8 @@ fn uninstall_skill(request: UninstallRequest) -> Result<ImportResult, String> {
             success: false,
             message: "Invalid skill path - must be in .claude/skills directory".to_string(),
             blocked: false,
+            security_report: None,
+            error_kind: None,
         });
     }
 
@@ -712,25 +753,76 @@ fn uninstall_skill(request: UninstallRequest) -> Result<ImportResult, String> {
             success: true,
             message: "Skill uninstalled successfully".to_string(),
             blocked: false,
+            security_report: None,
+            error_kind: None,
         }),
         Err(e) => Ok(ImportResult {
             success: false,
             message: format!("Failed to remove skill: {}", e),
             blocked: false,
+            security_report: None,
+            error_kind: None,
         }),
     }
 }
 
+// 安装目录名只允许单级普通目录名
+fn is_safe_dir_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+// 压缩包去掉扩展名；单个 SKILL.md 优先使用 frontmatter 中的 name，其次是文件名
+fn local_skill_name(source: &Path, requested: &str) -> String {
+    if archive::archive_kind(source).is_some() {
+        return archive::strip_archive_extension(requested).to_string();
+    }
+    if source.is_file() {
+        let frontmatter_name = fs::read_to_string(source)
+            .ok()
+            .and_then(|content| parse_yaml_frontmatter(&content).1)
+            .filter(|name| is_safe_dir_name(name));
+        if let Some(name) = frontmatter_name {
+            return name;
+        }
+        let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        if !stem.eq_ignore_ascii_case("SKILL") && is_safe_dir_name(&stem) {
+            return stem;
+        }
+    }
+    requested.to_string()
+}
+
+// 把目录、压缩包或单个 SKILL.md 准备成暂存区中的 skill 目录
+fn stage_local_source(source: &Path, staging_root: &Path, staged_skill: &Path) -> Result<(), String> {
+    if let Some(kind) = archive::archive_kind(source) {
+        let extract_dir = staging_root.join("extract");
+        archive::extract_archive(source, kind, &extract_dir)?;
+        let root = archive::unwrap_single_root(&extract_dir);
+        return fs::rename(&root, staged_skill).map_err(|e| format!("Failed to stage archive: {}", e));
+    }
+
+    if source.is_file() {
+        fs::create_dir_all(staged_skill).map_err(|e| e.to_string())?;
+        return fs::copy(source, staged_skill.join("SKILL.md"))
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy SKILL.md: {}", e));
+    }
+
+    copy_dir_all(&source.to_path_buf(), &staged_skill.to_path_buf())
+        .map_err(|e| format!("Failed to copy skill: {}", e))
+}
+
 #[tauri::command]
 fn import_local_skill(request: ImportLocalRequest) -> Result<ImportResult, String> {
     let source = PathBuf::from(&request.source_path);
 
     if !source.exists() {
-        return Ok(ImportResult {
-            success: false,
-            message: "Source path does not exist".to_string(),
-            blocked: false,
-        });
+        return Ok(failed_import("Source path does not exist".to_string()));
+    }
+
+    let skill_name = local_skill_name(&source, &request.skill_name);
+    if !is_safe_dir_name(&skill_name) {
+        return Ok(failed_import(format!("Invalid skill name: {}", skill_name)));
     }
 
     let install_dir = if let Some(path) = &request.install_path {
@@ -741,29 +833,35 @@ fn import_local_skill(request: ImportLocalRequest) -> Result<ImportResult, Strin
 
     fs::create_dir_all(&install_dir).map_err(|e| e.to_string())?;
 
-    let target_dir = install_dir.join(&request.skill_name);
+    let target_dir = install_dir.join(&skill_name);
 
-    copy_dir_all(&source, &target_dir).map_err(|e| e.to_string())?;
+    // 先复制到暂存目录，复制中途失败不会影响已安装的版本
+    let staging = match install::StagingDir::new(&install_dir, &skill_name) {
+        Ok(staging) => staging,
+        Err(e) => return Ok(failed_import(e)),
+    };
+    let staged_skill = staging.path().join(&skill_name);
+
+    if let Err(e) = stage_local_source(&source, staging.path(), &staged_skill) {
+        return Ok(failed_import(e));
+    }
 
     // 保存本地导入的元数据
     let metadata = SkillMetadata {
         source: "local".to_string(),
         source_url: None,
         install_date: current_timestamp(),
-        commit_hash: None,
-        version: None,
-        author: None,
-        description: None,
-        description_zh: None,
-        description_en: None,
+        ..Default::default()
     };
-    let _ = save_skill_metadata(&target_dir, &metadata);
 
-    Ok(ImportResult {
-        success: true,
-        message: format!("Successfully imported {} to {}", request.skill_name, target_dir.display()),
-        blocked: false,
-    })
+    Ok(install::finalize_install(
+        &staged_skill,
+        &target_dir,
+        &skill_name,
+        request.skip_security_check.unwrap_or(false),
+        &metadata,
+        None,
+    ))
 }
 
 fn copy_dir_all(src: &PathBuf, dst: &PathBuf) -> std::io::Result<()> {
@@ -780,46 +878,28 @@ fn copy_dir_all(src: &PathBuf, dst: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
-#[tauri::command]
-fn get_project_paths() -> Result<Vec<String>, String> {
+// 读取 skill-manager-config.json，文件不存在时返回空对象
+fn load_config() -> Result<serde_json::Value, String> {
     let config_path = get_config_path().ok_or("Cannot determine config path")?;
 
     if !config_path.exists() {
-        return Ok(Vec::new());
+        return Ok(serde_json::json!({}));
     }
 
     let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-    let config: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-
-    let paths = config
-        .get("projectPaths")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect()
-        })
-        .unwrap_or_default();
-
-    Ok(paths)
+    serde_json::from_str(&content).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn save_project_paths(request: SavePathsRequest) -> Result<(), String> {
+// 更新配置中的单个字段，保留其余字段
+fn set_config_value(key: &str, value: serde_json::Value) -> Result<(), String> {
     let config_path = get_config_path().ok_or("Cannot determine config path")?;
 
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    let mut config: serde_json::Value = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
-    } else {
-        serde_json::json!({})
-    };
-
-    config["projectPaths"] = serde_json::json!(request.paths);
+    let mut config = load_config().unwrap_or(serde_json::json!({}));
+    config[key] = value;
 
     fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
         .map_err(|e| e.to_string())?;
@@ -827,6 +907,32 @@ fn save_project_paths(request: SavePathsRequest) -> Result<(), String> {
     Ok(())
 }
 
+fn get_config_string_list(key: &str) -> Result<Vec<String>, String> {
+    let config = load_config()?;
+
+    let values = config
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(values)
+}
+
+#[tauri::command]
+fn get_project_paths() -> Result<Vec<String>, String> {
+    get_config_string_list("projectPaths")
+}
+
+#[tauri::command]
+fn save_project_paths(request: SavePathsRequest) -> Result<(), String> {
+    set_config_value("projectPaths", serde_json::json!(request.paths))
+}
+
 #[tauri::command]
 fn open_url(url: String) -> Result<(), String> {
     #[cfg(target_os = "macos")]
@@ -935,6 +1041,62 @@ fn scan_all_skills_security() -> Result<Vec<SecurityReport>, String> {
 
 // ========== 软链接管理 ==========
 
+#[derive(Debug, PartialEq)]
+enum LinkPathState {
+    Missing,
+    Symlink,
+    // 存在但不是符号链接（可能是普通目录）
+    Occupied,
+}
+
+// 不跟随链接检查路径状态，失效的软链接也视为 Symlink
+fn inspect_link_path(link_path: &Path) -> LinkPathState {
+    match fs::symlink_metadata(link_path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => LinkPathState::Symlink,
+        Ok(_) => LinkPathState::Occupied,
+        Err(_) => LinkPathState::Missing,
+    }
+}
+
+// 创建指向 source_dir 的目录软链接，旧软链接会被替换，真实目录绝不覆盖
+fn replace_dir_symlink(source_dir: &Path, link_path: &Path) -> Result<(), String> {
+    match inspect_link_path(link_path) {
+        LinkPathState::Occupied => {
+            return Err("Path exists and is not a symlink. Please remove it manually.".to_string());
+        }
+        LinkPathState::Symlink => remove_dir_symlink(link_path)?,
+        LinkPathState::Missing => {}
+    }
+
+    // 创建符号链接
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(source_dir, link_path)
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(windows)]
+    {
+        // Windows 需要管理员权限或开发者模式
+        std::os::windows::fs::symlink_dir(source_dir, link_path)
+            .map_err(|e| format!("Failed to create symlink (may need admin rights): {}", e))?;
+    }
+
+    Ok(())
+}
+
+// 删除软链接，路径不是软链接时拒绝删除
+fn remove_dir_symlink(link_path: &Path) -> Result<(), String> {
+    match inspect_link_path(link_path) {
+        LinkPathState::Missing => Ok(()),
+        LinkPathState::Occupied => Err("Path is not a symlink, refusing to remove".to_string()),
+        // Windows 上的目录软链接需要 remove_dir 删除
+        LinkPathState::Symlink => fs::remove_file(link_path)
+            .or_else(|_| fs::remove_dir(link_path))
+            .map_err(|e| e.to_string()),
+    }
+}
+
 #[tauri::command]
 fn get_all_agents() -> Result<Vec<AgentConfig>, String> {
     Ok(get_agent_configs())
@@ -1028,38 +1190,20 @@ fn create_symlink(agent_id: String) -> Result<SymlinkStatus, String> {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    // 如果路径已存在，检查是否是符号链接
-    if link_path.exists() {
-        let metadata = fs::symlink_metadata(&link_path).map_err(|e| e.to_string())?;
-        if metadata.file_type().is_symlink() {
-            // 已是符号链接，删除重建
-            fs::remove_file(&link_path).map_err(|e| e.to_string())?;
-        } else {
-            return Ok(SymlinkStatus {
-                agent_id: agent.id.clone(),
-                agent_name: agent.display_name.clone(),
-                target_path: source_dir.to_string_lossy().to_string(),
-                link_path: link_path.to_string_lossy().to_string(),
-                exists: true,
-                is_valid: false,
-                error: Some("Path exists and is not a symlink. Please remove it manually.".to_string()),
-            });
-        }
-    }
-
-    // 创建符号链接
-    #[cfg(unix)]
-    {
-        std::os::unix::fs::symlink(&source_dir, &link_path)
-            .map_err(|e| e.to_string())?;
+    // 已存在的真实目录不覆盖
+    if inspect_link_path(&link_path) == LinkPathState::Occupied {
+        return Ok(SymlinkStatus {
+            agent_id: agent.id.clone(),
+            agent_name: agent.display_name.clone(),
+            target_path: source_dir.to_string_lossy().to_string(),
+            link_path: link_path.to_string_lossy().to_string(),
+            exists: true,
+            is_valid: false,
+            error: Some("Path exists and is not a symlink. Please remove it manually.".to_string()),
+        });
     }
 
-    #[cfg(windows)]
-    {
-        // Windows 需要管理员权限或开发者模式
-        std::os::windows::fs::symlink_dir(&source_dir, &link_path)
-            .map_err(|e| format!("Failed to create symlink (may need admin rights): {}", e))?;
-    }
+    replace_dir_symlink(&source_dir, &link_path)?;
 
     Ok(SymlinkStatus {
         agent_id: agent.id.clone(),
@@ -1108,14 +1252,7 @@ fn remove_symlink(agent_id: String) -> Result<SymlinkStatus, String> {
 
     let link_path = home.join(&agent.global_skills_dir);
 
-    if link_path.exists() {
-        let metadata = fs::symlink_metadata(&link_path).map_err(|e| e.to_string())?;
-        if metadata.file_type().is_symlink() {
-            fs::remove_file(&link_path).map_err(|e| e.to_string())?;
-        } else {
-            return Err("Path is not a symlink, refusing to remove".to_string());
-        }
-    }
+    remove_dir_symlink(&link_path)?;
 
     Ok(SymlinkStatus {
         agent_id: agent.id.clone(),
@@ -1128,6 +1265,145 @@ fn remove_symlink(agent_id: String) -> Result<SymlinkStatus, String> {
     })
 }
 
+// ========== 自定义软链接 ==========
+
+#[derive(Debug, Deserialize)]
+pub struct CustomSymlinkRequest {
+    #[serde(rename = "targetPath")]
+    pub target_path: String,
+}
+
+// 自定义软链接状态，targetPath 为链接所在位置，sourcePath 为主目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomSymlinkStatus {
+    pub success: bool,
+    #[serde(rename = "targetPath")]
+    pub target_path: String,
+    #[serde(rename = "sourcePath")]
+    pub source_path: String,
+    pub exists: bool,
+    #[serde(rename = "isValid")]
+    pub is_valid: bool,
+    pub error: Option<String>,
+}
+
+// 展开以 ~ 开头的路径
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    if path.trim().is_empty() {
+        return Err("Target path is empty".to_string());
+    }
+    match path.strip_prefix('~') {
+        Some(rest) => {
+            let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+            Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn custom_symlink_status(target_path: &str, link_path: &Path, source_dir: &Path) -> CustomSymlinkStatus {
+    let (exists, is_valid, error) = match inspect_link_path(link_path) {
+        LinkPathState::Missing => (false, false, None),
+        LinkPathState::Symlink => match fs::read_link(link_path) {
+            Ok(target) if target == source_dir => (true, true, None),
+            Ok(target) => (true, false, Some(format!("Points to: {}", target.display()))),
+            Err(e) => (true, false, Some(e.to_string())),
+        },
+        LinkPathState::Occupied => (true, false, Some("Path exists but is not a symlink".to_string())),
+    };
+
+    CustomSymlinkStatus {
+        success: true,
+        target_path: target_path.to_string(),
+        source_path: source_dir.to_string_lossy().to_string(),
+        exists,
+        is_valid,
+        error,
+    }
+}
+
+// 自定义软链接路径持久化在配置的 customSymlinks 字段中
+#[tauri::command]
+fn get_custom_symlinks() -> Result<Vec<CustomSymlinkStatus>, String> {
+    let source_dir = get_claude_skills_dir().ok_or("Cannot determine skills directory")?;
+    let paths = get_config_string_list("customSymlinks")?;
+
+    let mut statuses = Vec::new();
+    for path in paths {
+        let status = match expand_home(&path) {
+            Ok(link_path) => custom_symlink_status(&path, &link_path, &source_dir),
+            Err(e) => CustomSymlinkStatus {
+                success: false,
+                target_path: path.clone(),
+                source_path: source_dir.to_string_lossy().to_string(),
+                exists: false,
+                is_valid: false,
+                error: Some(e),
+            },
+        };
+        statuses.push(status);
+    }
+
+    Ok(statuses)
+}
+
+#[tauri::command]
+fn save_custom_symlink_paths(request: SavePathsRequest) -> Result<(), String> {
+    set_config_value("customSymlinks", serde_json::json!(request.paths))
+}
+
+#[tauri::command]
+fn check_custom_symlink(request: CustomSymlinkRequest) -> Result<CustomSymlinkStatus, String> {
+    let source_dir = get_claude_skills_dir().ok_or("Cannot determine skills directory")?;
+    let link_path = expand_home(&request.target_path)?;
+
+    Ok(custom_symlink_status(&request.target_path, &link_path, &source_dir))
+}
+
+#[tauri::command]
+fn create_custom_symlink(request: CustomSymlinkRequest) -> Result<CustomSymlinkStatus, String> {
+    let source_dir = get_claude_skills_dir().ok_or("Cannot determine skills directory")?;
+    let link_path = expand_home(&request.target_path)?;
+
+    // 确保源目录和链接父目录存在
+    fs::create_dir_all(&source_dir).map_err(|e| e.to_string())?;
+    if let Some(parent) = link_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    if let Err(e) = replace_dir_symlink(&source_dir, &link_path) {
+        let mut status = custom_symlink_status(&request.target_path, &link_path, &source_dir);
+        status.success = false;
+        status.error = Some(e);
+        return Ok(status);
+    }
+
+    // 记录到配置，由后端统一管理
+    let mut paths = get_config_string_list("customSymlinks").unwrap_or_default();
+    if !paths.contains(&request.target_path) {
+        paths.push(request.target_path.clone());
+        set_config_value("customSymlinks", serde_json::json!(paths))?;
+    }
+
+    Ok(custom_symlink_status(&request.target_path, &link_path, &source_dir))
+}
+
+// 只删除链接本身，路径仍保留在配置中，需通过 save_custom_symlink_paths 移除
+#[tauri::command]
+fn remove_custom_symlink(request: CustomSymlinkRequest) -> Result<CustomSymlinkStatus, String> {
+    let source_dir = get_claude_skills_dir().ok_or("Cannot determine skills directory")?;
+    let link_path = expand_home(&request.target_path)?;
+
+    if let Err(e) = remove_dir_symlink(&link_path) {
+        let mut status = custom_symlink_status(&request.target_path, &link_path, &source_dir);
+        status.success = false;
+        status.error = Some(e);
+        return Ok(status);
+    }
+
+    Ok(custom_symlink_status(&request.target_path, &link_path, &source_dir))
+}
+
 // 获取平台信息
 #[tauri::command]
 fn get_platform_info() -> Result<serde_json::Value, String> {
@@ -1159,7 +1435,20 @@ pub fn run() {
             create_symlink,
             create_all_symlinks,
             remove_symlink,
-            get_platform_info
+            get_custom_symlinks,
+            save_custom_symlink_paths,
+            check_custom_symlink,
+            create_custom_symlink,
+            remove_custom_symlink,
+            get_platform_info,
+            update::check_skill_update,
+            discovery::discover_repo_skills,
+            discovery::install_discovered_skills,
+            discovery::release_discovery,
+            progress::cancel_install,
+            versions::list_skill_versions,
+            versions::diff_skill_version,
+            versions::restore_skill_version
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,169 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use crate::ImportResult;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallStage {
+    Resolving,
+    Cloning,
+    Scanning,
+    WritingMetadata,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstallProgress {
+    #[serde(rename = "installId")]
+    pub install_id: String,
+    pub stage: InstallStage,
+    pub percent: Option<u32>,
+    pub message: Option<String>,
+}
+
+// 一次安装的进度通道和取消状态，事件名为 install-progress:<installId>
+pub struct InstallTask {
+    install_id: String,
+    app: Option<AppHandle>,
+    cancelled: AtomicBool,
+    child_pid: Mutex<Option<u32>>,
+}
+
+lazy_static::lazy_static! {
+    static ref TASKS: Mutex<HashMap<String, Arc<InstallTask>>> = Mutex::new(HashMap::new());
+}
+
+fn is_valid_install_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl InstallTask {
+    pub fn emit(&self, stage: InstallStage, percent: Option<u32>, message: Option<String>) {
+        if let Some(app) = &self.app {
+            let payload = InstallProgress {
+                install_id: self.install_id.clone(),
+                stage,
+                percent,
+                message,
+            };
+            let _ = app.emit(&format!("install-progress:{}", self.install_id), payload);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    // 记录正在运行的 git 子进程，取消时终止它
+    pub fn set_child(&self, pid: Option<u32>) {
+        if let Ok(mut child) = self.child_pid.lock() {
+            *child = pid;
+        }
+        // 子进程启动前已被取消时立即终止
+        if let Some(pid) = pid {
+            if self.is_cancelled() {
+                kill_process_tree(pid);
+            }
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(pid) = self.child_pid.lock().ok().and_then(|p| *p) {
+            kill_process_tree(pid);
+        }
+    }
+
+    // 根据安装结果发送最终事件
+    pub fn finish(&self, result: &ImportResult) {
+        let stage = if result.success {
+            InstallStage::Done
+        } else if result.error_kind.as_deref() == Some("cancelled") {
+            InstallStage::Cancelled
+        } else {
+            InstallStage::Failed
+        };
+        self.emit(stage, None, Some(result.message.clone()));
+    }
+}
+
+// git clone 会再启动 git-remote-https、index-pack 等子进程，需要连同子进程一起终止
+fn kill_process_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        // 子进程以自己为进程组长启动，向整个进程组发送信号
+        let _ = Command::new("kill").args(["-TERM", &format!("-{}", pid)]).status();
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill").args(["/T", "/F", "/PID", &pid.to_string()]).status();
+    }
+}
+
+// 让子进程成为独立的进程组，便于取消时整组终止
+pub fn isolate_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = cmd;
+    }
+}
+
+// 未提供 installId 时返回不发送事件、也无法取消的任务
+pub fn register(app: &AppHandle, install_id: Option<String>) -> Result<Arc<InstallTask>, String> {
+    let install_id = match install_id {
+        Some(id) if !is_valid_install_id(&id) => return Err(format!("Invalid install id: {}", id)),
+        Some(id) => id,
+        None => {
+            return Ok(Arc::new(InstallTask {
+                install_id: String::new(),
+                app: None,
+                cancelled: AtomicBool::new(false),
+                child_pid: Mutex::new(None),
+            }))
+        }
+    };
+
+    let task = Arc::new(InstallTask {
+        install_id: install_id.clone(),
+        app: Some(app.clone()),
+        cancelled: AtomicBool::new(false),
+        child_pid: Mutex::new(None),
+    });
+    let mut tasks = TASKS.lock().map_err(|e| e.to_string())?;
+    if tasks.contains_key(&install_id) {
+        return Err(format!("Install {} is already running", install_id));
+    }
+    tasks.insert(install_id, task.clone());
+    Ok(task)
+}
+
+pub fn unregister(task: &InstallTask) {
+    if let Ok(mut tasks) = TASKS.lock() {
+        tasks.remove(&task.install_id);
+    }
+}
+
+// 取消正在进行的安装：终止 git 子进程，暂存目录由安装流程在退出时清理
+#[tauri::command]
+pub fn cancel_install(install_id: String) -> Result<bool, String> {
+    let task = TASKS.lock().map_err(|e| e.to_string())?.get(&install_id).cloned();
+    match task {
+        Some(task) => {
+            task.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
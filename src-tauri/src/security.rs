@@ -755,8 +755,8 @@ pub fn scan_directory(dir_path: &Path, skill_id: &str) -> Result<SecurityReport>
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| {
-            // 跳过隐藏目录和 node_modules
-            let path = e.path();
+            // 跳过隐藏目录和 node_modules，只检查扫描根目录以下的部分（根目录本身可能位于 ~/.claude 下）
+            let path = e.path().strip_prefix(dir_path).unwrap_or(e.path());
             !path.components().any(|c| {
                 let s = c.as_os_str().to_string_lossy();
                 s.starts_with('.') || s == "node_modules" || s == "__pycache__" || s == "target"
@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+#[derive(Debug, Deserialize)]
+pub struct CheckUpdateRequest {
+    #[serde(rename = "skillPath")]
+    pub skill_path: String,
+    #[serde(rename = "sourceUrl")]
+    pub source_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateCheckResult {
+    #[serde(rename = "hasUpdate")]
+    pub has_update: bool,
+    #[serde(rename = "currentCommitHash")]
+    pub current_commit_hash: Option<String>,
+    #[serde(rename = "latestCommitHash")]
+    pub latest_commit_hash: String,
+    // 本地版本落后的提交数，/tree/ 安装只统计涉及子路径的提交
+    #[serde(rename = "commitsBehind")]
+    pub commits_behind: Option<u32>,
+    // 固定到标签的安装：比当前标签更新的版本标签，从新到旧
+    #[serde(rename = "newerTags")]
+    pub newer_tags: Vec<String>,
+}
+
+#[tauri::command(async)]
+pub async fn check_skill_update(request: CheckUpdateRequest) -> Result<UpdateCheckResult, String> {
+    tokio::task::spawn_blocking(move || {
+        check_update(&PathBuf::from(&request.skill_path), &request.source_url)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub fn check_update(skill_dir: &PathBuf, source_url: &str) -> Result<UpdateCheckResult, String> {
+    let metadata = crate::load_skill_metadata(skill_dir)
+        .ok_or("No .skill-meta.json found for this skill")?;
+    let current = metadata
+        .commit_hash
+        .clone()
+        .ok_or("No commit hash recorded for this skill")?;
+
+    let source = crate::source_url::parse_source_url(source_url)?;
+    let repo_url = source.clone_url;
+    // 元数据中记录的子路径相对于仓库根目录，优先于 URL 中的路径
+    let subpath = metadata.subpath.clone().or(source.subpath);
+
+    // 私有仓库沿用安装时的认证方式
+    let auth = metadata.auth.clone().unwrap_or_default();
+
+    let pinned = match (&metadata.ref_type, &metadata.git_ref) {
+        (Some(kind), Some(name)) => git::GitRef::from_parts(kind, name),
+        _ => None,
+    };
+
+    let branch = match pinned {
+        // 固定到 commit 的安装不会有更新
+        Some(git::GitRef::Commit(_)) => {
+            return Ok(UpdateCheckResult {
+                has_update: false,
+                current_commit_hash: Some(current.clone()),
+                latest_commit_hash: current,
+                commits_behind: Some(0),
+                newer_tags: Vec::new(),
+            });
+        }
+        Some(git::GitRef::Tag(tag)) => return check_tag_update(&repo_url, &tag, current, &auth),
+        Some(git::GitRef::Branch(b)) => Some(b),
+        None => source.git_ref,
+    };
+
+    let latest = git::ls_remote(&repo_url, branch.as_deref(), &auth)?;
+
+    if latest == current {
+        return Ok(UpdateCheckResult {
+            has_update: false,
+            current_commit_hash: Some(current),
+            latest_commit_hash: latest,
+            commits_behind: Some(0),
+            newer_tags: Vec::new(),
+        });
+    }
+
+    // 远程已前进：拉取不含文件内容的历史，用于计算落后提交数和子路径的树哈希
+    let fetch_dir = std::env::temp_dir().join(format!(
+        "skill-manager-update-{}-{}",
+        std::process::id(),
+        crate::current_timestamp()
+    ));
+    let fetch_ref = match &branch {
+        Some(b) => format!("refs/heads/{}", b),
+        None => "HEAD".to_string(),
+    };
+    let result = compare_with_remote(
+        &fetch_dir,
+        &repo_url,
+        &fetch_ref,
+        &current,
+        subpath.as_deref(),
+        metadata.tree_hash.as_deref(),
+        &auth,
+    );
+    let _ = fs::remove_dir_all(&fetch_dir);
+
+    let (has_update, commits_behind) = result?;
+
+    Ok(UpdateCheckResult {
+        has_update,
+        current_commit_hash: Some(current),
+        latest_commit_hash: latest,
+        commits_behind,
+        newer_tags: Vec::new(),
+    })
+}
+
+// 标签固定的安装只在出现更高版本号的标签时提示更新
+fn check_tag_update(repo_url: &str, tag: &str, current: String, auth: &git::GitAuth) -> Result<UpdateCheckResult, String> {
+    let current_version = git::parse_version_tag(tag)
+        .ok_or_else(|| format!("Tag {} is not a version number, cannot look for newer tags", tag))?;
+
+    let mut newer: Vec<(Vec<u64>, String, String)> = git::list_remote_tags(repo_url, auth)?
+        .into_iter()
+        .filter_map(|(name, hash)| {
+            let version = git::parse_version_tag(&name)?;
+            (version > current_version).then_some((version, name, hash))
+        })
+        .collect();
+    newer.sort_by(|a, b| b.0.cmp(&a.0));
+
+    Ok(UpdateCheckResult {
+        has_update: !newer.is_empty(),
+        latest_commit_hash: newer.first().map(|n| n.2.clone()).unwrap_or_else(|| current.clone()),
+        current_commit_hash: Some(current),
+        commits_behind: None,
+        newer_tags: newer.into_iter().map(|n| n.1).collect(),
+    })
+}
+
+fn compare_with_remote(
+    fetch_dir: &Path,
+    repo_url: &str,
+    fetch_ref: &str,
+    current: &str,
+    subpath: Option<&str>,
+    current_tree: Option<&str>,
+    auth: &git::GitAuth,
+) -> Result<(bool, Option<u32>), String> {
+    fs::create_dir_all(fetch_dir).map_err(|e| e.to_string())?;
+    git::run_git(Some(fetch_dir), &["init", "--bare", "--quiet"])?;
+    git::run_git_auth(
+        Some(fetch_dir),
+        &["fetch", "--quiet", "--no-tags", "--filter=blob:none", repo_url, fetch_ref],
+        auth,
+    )?;
+
+    // 本地 commit 可能已被强推覆盖，此时无法计算落后数
+    let range = format!("{}..FETCH_HEAD", current);
+    let mut args = vec!["rev-list", "--count", range.as_str()];
+    if let Some(sub) = subpath {
+        args.push("--");
+        args.push(sub);
+    }
+    let commits_behind = git::run_git(Some(fetch_dir), &args)
+        .ok()
+        .and_then(|count| count.parse().ok());
+
+    let has_update = match (subpath, current_tree) {
+        (Some(sub), Some(tree)) => {
+            let latest_tree = git::run_git(Some(fetch_dir), &["rev-parse", &format!("FETCH_HEAD:{}", sub)])?;
+            latest_tree != tree
+        }
+        (Some(_), None) => commits_behind != Some(0),
+        _ => true,
+    };
+
+    Ok((has_update, commits_behind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SkillMetadata;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let mut full = vec!["-c", "user.name=test", "-c", "user.email=test@example.com"];
+        full.extend_from_slice(args);
+        git::run_git(Some(dir), &full).unwrap()
+    }
+
+    fn commit_file(work: &Path, rel: &str, content: &str) -> String {
+        let file = work.join(rel);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, content).unwrap();
+        git(work, &["add", "-A"]);
+        git(work, &["commit", "--quiet", "-m", rel]);
+        git(work, &["push", "--quiet", "origin", "HEAD:main"]);
+        git(work, &["rev-parse", "HEAD"])
+    }
+
+    // 创建本地裸仓库代替 GitHub，返回 (临时目录, 裸仓库地址, 工作区)
+    fn setup_remote() -> (tempfile::TempDir, String, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let remote = tmp.path().join("remote.git");
+        let work = tmp.path().join("work");
+        fs::create_dir_all(&remote).unwrap();
+        fs::create_dir_all(&work).unwrap();
+        git(&remote, &["init", "--bare", "--quiet"]);
+        git(&remote, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        git(&work, &["init", "--quiet"]);
+        git(&work, &["remote", "add", "origin", remote.to_str().unwrap()]);
+        let url = remote.to_string_lossy().to_string();
+        (tmp, url, work)
+    }
+
+    fn install_meta(dir: &Path, commit: &str, tree: Option<String>) -> PathBuf {
+        let skill_dir = dir.join("installed");
+        fs::create_dir_all(&skill_dir).unwrap();
+        let metadata = SkillMetadata {
+            source: "github".to_string(),
+            source_url: None,
+            install_date: 0,
+            commit_hash: Some(commit.to_string()),
+            tree_hash: tree,
+            ..Default::default()
+        };
+        crate::save_skill_metadata(&skill_dir, &metadata).unwrap();
+        skill_dir
+    }
+
+    #[test]
+    fn up_to_date_repo_has_no_update() {
+        let (tmp, url, work) = setup_remote();
+        let head = commit_file(&work, "SKILL.md", "v1");
+        let skill_dir = install_meta(tmp.path(), &head, None);
+
+        let result = check_update(&skill_dir, &url).unwrap();
+        assert!(!result.has_update);
+        assert_eq!(result.latest_commit_hash, head);
+        assert_eq!(result.commits_behind, Some(0));
+    }
+
+    #[test]
+    fn new_commits_are_counted() {
+        let (tmp, url, work) = setup_remote();
+        let installed = commit_file(&work, "SKILL.md", "v1");
+        commit_file(&work, "SKILL.md", "v2");
+        let head = commit_file(&work, "README.md", "readme");
+        let skill_dir = install_meta(tmp.path(), &installed, None);
+
+        let result = check_update(&skill_dir, &url).unwrap();
+        assert!(result.has_update);
+        assert_eq!(result.latest_commit_hash, head);
+        assert_eq!(result.commits_behind, Some(2));
+    }
+
+    #[test]
+    fn tree_install_only_tracks_its_subpath() {
+        let (tmp, url, work) = setup_remote();
+        let installed = commit_file(&work, "skills/foo/SKILL.md", "v1");
+        let tree = git(&work, &["rev-parse", "HEAD:skills/foo"]);
+        let skill_dir = install_meta(tmp.path(), &installed, Some(tree));
+        let tree_url = format!("{}/tree/main/skills/foo", url);
+
+        commit_file(&work, "skills/bar/SKILL.md", "other skill");
+        let result = check_update(&skill_dir, &tree_url).unwrap();
+        assert!(!result.has_update);
+        assert_eq!(result.commits_behind, Some(0));
+
+        commit_file(&work, "skills/foo/SKILL.md", "v2");
+        let result = check_update(&skill_dir, &tree_url).unwrap();
+        assert!(result.has_update);
+        assert_eq!(result.commits_behind, Some(1));
+    }
+
+    #[test]
+    fn tag_pin_reports_newer_version_tags() {
+        let (tmp, url, work) = setup_remote();
+        let installed = commit_file(&work, "SKILL.md", "v1");
+        git(&work, &["tag", "v1.0.0"]);
+        commit_file(&work, "SKILL.md", "v2");
+        git(&work, &["tag", "-a", "v1.1.0", "-m", "release"]);
+        git(&work, &["tag", "nightly"]);
+        git(&work, &["push", "--quiet", "origin", "--tags"]);
+        let tagged = git(&work, &["rev-parse", "HEAD"]);
+
+        let skill_dir = install_meta(tmp.path(), &installed, None);
+        let mut metadata = crate::load_skill_metadata(&skill_dir).unwrap();
+        metadata.git_ref = Some("v1.0.0".to_string());
+        metadata.ref_type = Some("tag".to_string());
+        crate::save_skill_metadata(&skill_dir, &metadata).unwrap();
+
+        let result = check_update(&skill_dir, &url).unwrap();
+        assert!(result.has_update);
+        assert_eq!(result.newer_tags, vec!["v1.1.0".to_string()]);
+        assert_eq!(result.latest_commit_hash, tagged);
+    }
+
+    #[test]
+    fn commit_pin_never_updates() {
+        let (tmp, url, work) = setup_remote();
+        let installed = commit_file(&work, "SKILL.md", "v1");
+        commit_file(&work, "SKILL.md", "v2");
+
+        let skill_dir = install_meta(tmp.path(), &installed, None);
+        let mut metadata = crate::load_skill_metadata(&skill_dir).unwrap();
+        metadata.git_ref = Some(installed.clone());
+        metadata.ref_type = Some("commit".to_string());
+        crate::save_skill_metadata(&skill_dir, &metadata).unwrap();
+
+        let result = check_update(&skill_dir, &url).unwrap();
+        assert!(!result.has_update);
+        assert_eq!(result.latest_commit_hash, installed);
+    }
+
+    #[test]
+    fn missing_metadata_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(check_update(&tmp.path().to_path_buf(), "https://example.com/repo").is_err());
+    }
+}
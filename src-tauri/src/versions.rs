@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::{failed_import, install, ImportResult, SkillMetadata};
+
+// 每个 skill 默认保留的历史版本数，可通过配置 versionHistoryLimit 修改
+const DEFAULT_HISTORY_LIMIT: usize = 5;
+
+// 归档信息，与快照内容一起保存
+const VERSION_INFO_FILE: &str = ".version-info.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct VersionInfo {
+    #[serde(rename = "archivedAt")]
+    archived_at: u64,
+    #[serde(rename = "originalPath")]
+    original_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SkillVersionRequest {
+    #[serde(rename = "skillPath")]
+    pub skill_path: String,
+    #[serde(rename = "versionId")]
+    pub version_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillVersion {
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    #[serde(rename = "archivedAt")]
+    pub archived_at: u64,
+    pub path: String,
+    pub metadata: Option<SkillMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillVersionDiff {
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    // 相对当前安装版本：历史版本中新增 / 缺少 / 内容不同的文件
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+fn get_versions_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".claude").join("skill-manager").join("versions"))
+}
+
+fn history_limit() -> usize {
+    crate::load_config()
+        .ok()
+        .and_then(|c| c.get("versionHistoryLimit").and_then(|v| v.as_u64()))
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+}
+
+fn skill_name_of(skill_path: &Path) -> Result<String, String> {
+    skill_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid skill path: {}", skill_path.display()))
+}
+
+// 跨文件系统时 rename 会失败，退化为复制后删除
+fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    crate::copy_dir_all(&from.to_path_buf(), &to.to_path_buf()).map_err(|e| e.to_string())?;
+    fs::remove_dir_all(from).map_err(|e| e.to_string())
+}
+
+fn read_version_info(dir: &Path) -> Option<VersionInfo> {
+    let content = fs::read_to_string(dir.join(VERSION_INFO_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+// 列出某个安装位置的所有历史版本，按归档时间从新到旧
+fn versions_for(skill_path: &Path) -> Result<Vec<SkillVersion>, String> {
+    let versions_dir = get_versions_dir().ok_or("Cannot determine versions directory")?;
+    let skill_dir = versions_dir.join(skill_name_of(skill_path)?);
+    let original = skill_path.to_string_lossy().to_string();
+
+    let mut versions = Vec::new();
+    if let Ok(entries) = fs::read_dir(&skill_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let info = match read_version_info(&path) {
+                Some(info) if info.original_path == original => info,
+                _ => continue,
+            };
+            versions.push(SkillVersion {
+                version_id: entry.file_name().to_string_lossy().to_string(),
+                archived_at: info.archived_at,
+                metadata: crate::load_skill_metadata(&path),
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    versions.sort_by_key(|v| std::cmp::Reverse(v.archived_at));
+    Ok(versions)
+}
+
+// 将被替换下来的旧版本移入版本库，超出保留数量的最旧版本会被删除
+pub fn archive_version(old_dir: &Path, skill_path: &Path) -> Result<(), String> {
+    let versions_dir = get_versions_dir().ok_or("Cannot determine versions directory")?;
+    let skill_dir = versions_dir.join(skill_name_of(skill_path)?);
+    fs::create_dir_all(&skill_dir).map_err(|e| e.to_string())?;
+
+    let now = crate::current_timestamp();
+    let original = skill_path.to_string_lossy().to_string();
+    let commit = crate::load_skill_metadata(&old_dir.to_path_buf()).and_then(|m| m.commit_hash);
+
+    // 同一安装位置的同一 commit 只保留一份，其余情况追加时间戳避免冲突
+    let mut version_id = commit.clone().unwrap_or_else(|| format!("local-{}", now));
+    let existing = skill_dir.join(&version_id);
+    if existing.exists() {
+        let same_origin = read_version_info(&existing)
+            .map(|info| info.original_path == original)
+            .unwrap_or(false);
+        if same_origin {
+            fs::remove_dir_all(&existing).map_err(|e| e.to_string())?;
+        } else {
+            version_id = format!("{}-{}", version_id, now);
+        }
+    }
+
+    let version_dir = skill_dir.join(&version_id);
+    move_dir(old_dir, &version_dir)?;
+
+    let info = VersionInfo {
+        archived_at: now,
+        original_path: original,
+    };
+    let content = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+    fs::write(version_dir.join(VERSION_INFO_FILE), content).map_err(|e| e.to_string())?;
+
+    let limit = history_limit();
+    for stale in versions_for(skill_path)?.into_iter().skip(limit) {
+        let _ = fs::remove_dir_all(&stale.path);
+    }
+
+    Ok(())
+}
+
+fn find_version(skill_path: &Path, version_id: Option<&str>) -> Result<SkillVersion, String> {
+    let version_id = version_id.ok_or("Version id is required")?;
+    versions_for(skill_path)?
+        .into_iter()
+        .find(|v| v.version_id == version_id)
+        .ok_or_else(|| format!("Version {} not found", version_id))
+}
+
+// 收集目录下所有文件的相对路径和内容，忽略元数据、版本记录文件和 .git
+fn collect_files(dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = match entry.path().strip_prefix(dir) {
+            Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        if rel == VERSION_INFO_FILE || rel == ".skill-meta.json" {
+            continue;
+        }
+        if let Ok(content) = fs::read(entry.path()) {
+            files.insert(rel, content);
+        }
+    }
+    files
+}
+
+#[tauri::command]
+pub fn list_skill_versions(request: SkillVersionRequest) -> Result<Vec<SkillVersion>, String> {
+    versions_for(&PathBuf::from(&request.skill_path))
+}
+
+#[tauri::command]
+pub fn diff_skill_version(request: SkillVersionRequest) -> Result<SkillVersionDiff, String> {
+    let skill_path = PathBuf::from(&request.skill_path);
+    let version = find_version(&skill_path, request.version_id.as_deref())?;
+
+    let current = collect_files(&skill_path);
+    let archived = collect_files(Path::new(&version.path));
+
+    let added = archived.keys().filter(|k| !current.contains_key(*k)).cloned().collect();
+    let removed = current.keys().filter(|k| !archived.contains_key(*k)).cloned().collect();
+    let modified = archived
+        .iter()
+        .filter(|(k, v)| current.get(*k).map(|c| c != *v).unwrap_or(false))
+        .map(|(k, _)| k.clone())
+        .collect();
+
+    Ok(SkillVersionDiff {
+        version_id: version.version_id,
+        added,
+        removed,
+        modified,
+    })
+}
+
+// 恢复历史版本：复制到暂存目录后原子替换，当前版本同样会被归档，可以再次回退
+#[tauri::command]
+pub fn restore_skill_version(request: SkillVersionRequest) -> Result<ImportResult, String> {
+    let skill_path = PathBuf::from(&request.skill_path);
+    let version = find_version(&skill_path, request.version_id.as_deref())?;
+    let skill_name = skill_name_of(&skill_path)?;
+    let install_dir = skill_path.parent().ok_or("Invalid skill path")?;
+
+    let staging = match install::StagingDir::new(install_dir, &skill_name) {
+        Ok(staging) => staging,
+        Err(e) => return Ok(failed_import(e)),
+    };
+    let staged_skill = staging.path().join(&skill_name);
+
+    if let Err(e) = crate::copy_dir_all(&PathBuf::from(&version.path), &staged_skill) {
+        return Ok(failed_import(format!("Failed to copy version: {}", e)));
+    }
+    let _ = fs::remove_file(staged_skill.join(VERSION_INFO_FILE));
+
+    if let Err(e) = install::swap_into_place(&staged_skill, &skill_path) {
+        return Ok(failed_import(e));
+    }
+
+    Ok(ImportResult {
+        success: true,
+        message: format!("Restored {} to version {}", skill_name, version.version_id),
+        blocked: false,
+        security_report: None,
+        error_kind: None,
+    })
+}
@@ -743,7 +743,7 @@ const MySkills = () => {
                   <div className="form-control">
                     <label className="label">
                       <span className="label-text font-semibold">
-                        {i18n.language === 'zh' ? '本地文件夹路径' : 'Local Folder Path'}
+                        {i18n.language === 'zh' ? '本地文件夹、压缩包或 SKILL.md 路径' : 'Local Folder, Archive or SKILL.md Path'}
                       </span>
                     </label>
                     <input
@@ -757,8 +757,8 @@ const MySkills = () => {
                     <label className="label">
                       <span className="label-text-alt text-base-content/50">
                         {i18n.language === 'zh'
-                          ? '文件夹必须包含 SKILL.md 文件'
-                          : 'Folder must contain SKILL.md file'}
+                          ? '支持文件夹、.zip / .tar.gz 压缩包或单个 SKILL.md 文件'
+                          : 'Accepts a folder, a .zip / .tar.gz archive or a single SKILL.md file'}
                       </span>
                     </label>
                   </div>
@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
-import type { InstalledSkill, MarketplaceSkill } from '../types';
+import type { GitAuth, InstalledSkill, MarketplaceSkill, RepoDiscovery } from '../types';
 import { invoke } from '@tauri-apps/api/core';
 
 interface AgentConfig {
@@ -73,8 +73,11 @@ interface SkillStore {
   installSkill: (skill: MarketplaceSkill) => Promise<InstallResult>;
   uninstallSkill: (id: string) => void;
   updateSkill: (id: string, skill: Partial<InstalledSkill>) => void;
-  importFromGithub: (url: string, installPath?: string) => Promise<InstallResult>;
+  importFromGithub: (url: string, installPath?: string, auth?: GitAuth) => Promise<InstallResult>;
   importFromLocal: (sourcePath: string, installPath?: string) => Promise<InstallResult>;
+  discoverRepoSkills: (url: string, auth?: GitAuth) => Promise<RepoDiscovery>;
+  installDiscoveredSkills: (discoveryId: string, subpaths: string[], installPath?: string) => Promise<{ success: string[]; failed: string[] }>;
+  releaseDiscovery: (discoveryId: string) => Promise<void>;
   fetchProjectPaths: () => Promise<void>;
   saveProjectPaths: (paths: string[]) => Promise<void>;
   setDefaultInstallLocation: (location: 'system' | 'project') => void;
@@ -100,8 +103,8 @@ interface SkillStore {
   createCustomSymlink: (targetPath: string) => Promise<boolean>;
   removeCustomSymlink: (targetPath: string) => Promise<boolean>;
   checkCustomSymlinks: () => Promise<void>;
-  addCustomSymlinkPath: (path: string) => void;
-  removeCustomSymlinkPath: (path: string) => void;
+  addCustomSymlinkPath: (path: string) => Promise<void>;
+  removeCustomSymlinkPath: (path: string) => Promise<void>;
 }
 
 export const useSkillStore = create<SkillStore>()(
@@ -248,6 +251,9 @@ export const useSkillStore = create<SkillStore>()(
             source: s.source || 'local',
             sourceUrl: s.sourceUrl,
             commitHash: s.commitHash,
+            ref: s.ref,
+            refType: s.refType,
+            subpath: s.subpath,
             hasUpdate: false
           });
 
@@ -299,6 +305,17 @@ export const useSkillStore = create<SkillStore>()(
         // 重新扫描本地技能
         await get().scanLocalSkills();
 
+        // 后端在安装前已完成安全扫描
+        if (result.securityReport) {
+          set({ lastSecurityReport: result.securityReport });
+          return {
+            success: true,
+            message: result.message,
+            blocked: false,
+            securityReport: result.securityReport
+          };
+        }
+
         // 安装后立即进行安全扫描
         set({ isScanning: true });
         try {
@@ -367,7 +384,7 @@ export const useSkillStore = create<SkillStore>()(
         }));
       },
 
-      importFromGithub: async (url: string, installPath?: string) => {
+      importFromGithub: async (url: string, installPath?: string, auth?: GitAuth) => {
         const { defaultInstallLocation, projectPaths, selectedProjectIndex } = get();
 
         let finalInstallPath = installPath;
@@ -379,7 +396,8 @@ export const useSkillStore = create<SkillStore>()(
           request: {
             repoUrl: url,
             installPath: finalInstallPath,
-            skipSecurityCheck: false
+            skipSecurityCheck: false,
+            auth
           }
         });
 
@@ -390,6 +408,17 @@ export const useSkillStore = create<SkillStore>()(
         // 重新扫描
         await get().scanLocalSkills();
 
+        // 后端在安装前已完成安全扫描
+        if (result.securityReport) {
+          set({ lastSecurityReport: result.securityReport });
+          return {
+            success: true,
+            message: result.message,
+            blocked: false,
+            securityReport: result.securityReport
+          };
+        }
+
         // 安装后立即进行安全扫描
         set({ isScanning: true });
         try {
@@ -442,6 +471,17 @@ export const useSkillStore = create<SkillStore>()(
         // 重新扫描
         await get().scanLocalSkills();
 
+        // 后端在安装前已完成安全扫描（压缩包导入时目录名与文件名不同，只能使用后端结果）
+        if (result.securityReport) {
+          set({ lastSecurityReport: result.securityReport });
+          return {
+            success: true,
+            message: result.message,
+            blocked: false,
+            securityReport: result.securityReport
+          };
+        }
+
         // 安装后立即进行安全扫描
         set({ isScanning: true });
         try {
@@ -475,6 +515,44 @@ export const useSkillStore = create<SkillStore>()(
         };
       },
 
+      // 克隆一次仓库，列出其中所有包含 SKILL.md 的目录
+      discoverRepoSkills: async (url: string, auth?: GitAuth) => {
+        const result: RepoDiscovery = await invoke('discover_repo_skills', {
+          request: { repoUrl: url, auth }
+        });
+        return result;
+      },
+
+      // 从已发现的仓库中安装选中的子目录，安装后克隆即被释放
+      installDiscoveredSkills: async (discoveryId: string, subpaths: string[], installPath?: string) => {
+        const { defaultInstallLocation, projectPaths, selectedProjectIndex } = get();
+
+        let finalInstallPath = installPath;
+        if (!finalInstallPath && defaultInstallLocation === 'project' && projectPaths.length > 0) {
+          finalInstallPath = projectPaths[selectedProjectIndex] || projectPaths[0];
+        }
+
+        const results: any[] = await invoke('install_discovered_skills', {
+          request: {
+            discoveryId,
+            subpaths,
+            installPath: finalInstallPath,
+            skipSecurityCheck: false
+          }
+        });
+
+        await get().scanLocalSkills();
+
+        return {
+          success: results.filter(r => r.success).map(r => r.subpath),
+          failed: results.filter(r => !r.success).map(r => r.subpath)
+        };
+      },
+
+      releaseDiscovery: async (discoveryId: string) => {
+        await invoke('release_discovery', { discoveryId });
+      },
+
       fetchProjectPaths: async () => {
         try {
           const paths: string[] = await invoke('get_project_paths');
@@ -520,6 +598,7 @@ export const useSkillStore = create<SkillStore>()(
                     ...skill,
                     hasUpdate: true,
                     latestCommitHash: result.latestCommitHash,
+                    latestTag: result.newerTags?.[0],
                     lastChecked: Date.now()
                   };
                 } else {
@@ -577,17 +656,15 @@ export const useSkillStore = create<SkillStore>()(
         }
 
         try {
-          // 先删除
-          await invoke('uninstall_skill', {
-            request: { skillPath: skill.localPath }
-          });
-
-          // 重新安装
+          // 后端会原子替换旧版本，安装失败时旧版本保持不变
           const result: any = await invoke('import_github_skill', {
             request: {
               repoUrl: skill.sourceUrl,
               installPath: skill.type === 'project' ? skill.localPath?.split('/.claude/skills')[0] : undefined,
-              skipSecurityCheck: false
+              skipSecurityCheck: false,
+              // 分支固定时跟随最新提交，标签有更新时切换到最新标签
+              ref: skill.refType === 'tag' ? (skill.latestTag ?? skill.ref) : skill.ref,
+              subpath: skill.subpath
             }
           });
 
@@ -598,17 +675,25 @@ export const useSkillStore = create<SkillStore>()(
         }
       },
 
-      // 自定义软链接 Actions
-      addCustomSymlinkPath: (path: string) => {
+      // 自定义软链接 Actions（路径由后端保存在 skill-manager-config.json）
+      addCustomSymlinkPath: async (path: string) => {
         const { customSymlinks } = get();
         if (!customSymlinks.find(s => s.path === path)) {
-          set({ customSymlinks: [...customSymlinks, { path, exists: false }] });
+          const updated = [...customSymlinks, { path, exists: false }];
+          await invoke('save_custom_symlink_paths', {
+            request: { paths: updated.map(s => s.path) }
+          });
+          set({ customSymlinks: updated });
         }
       },
 
-      removeCustomSymlinkPath: (path: string) => {
+      removeCustomSymlinkPath: async (path: string) => {
         const { customSymlinks } = get();
-        set({ customSymlinks: customSymlinks.filter(s => s.path !== path) });
+        const updated = customSymlinks.filter(s => s.path !== path);
+        await invoke('save_custom_symlink_paths', {
+          request: { paths: updated.map(s => s.path) }
+        });
+        set({ customSymlinks: updated });
       },
 
       createCustomSymlink: async (targetPath: string) => {
@@ -639,20 +724,10 @@ export const useSkillStore = create<SkillStore>()(
 
       checkCustomSymlinks: async () => {
         try {
-          const { customSymlinks } = get();
-          const updated = await Promise.all(
-            customSymlinks.map(async (s) => {
-              try {
-                const result: any = await invoke('check_custom_symlink', {
-                  request: { targetPath: s.path }
-                });
-                return { path: s.path, exists: result.exists };
-              } catch {
-                return { path: s.path, exists: false };
-              }
-            })
-          );
-          set({ customSymlinks: updated });
+          const statuses: any[] = await invoke('get_custom_symlinks');
+          set({
+            customSymlinks: statuses.map((s) => ({ path: s.targetPath, exists: s.exists }))
+          });
         } catch (error) {
           console.error('Failed to check custom symlinks:', error);
         }
@@ -663,8 +738,7 @@ export const useSkillStore = create<SkillStore>()(
       partialize: (state) => ({
         projectPaths: state.projectPaths,
         defaultInstallLocation: state.defaultInstallLocation,
-        selectedProjectIndex: state.selectedProjectIndex,
-        customSymlinks: state.customSymlinks
+        selectedProjectIndex: state.selectedProjectIndex
       }),
     }
   )
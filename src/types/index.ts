@@ -36,6 +36,25 @@ export interface InstalledSkill extends Partial<MarketplaceSkill> {
   lastChecked?: number;                          // 上次检查更新时间
   commitHash?: string;                           // 安装时的 commit hash
   latestCommitHash?: string;                     // 最新的 commit hash
+  ref?: string;                                  // 固定的分支、标签或 commit
+  refType?: 'branch' | 'tag' | 'commit';         // 引用类型
+  latestTag?: string;                            // 标签固定时可用的最新标签
+  subpath?: string;                              // 仓库内的 skill 子目录
+}
+
+// 仓库中发现的 skill
+export interface DiscoveredSkill {
+  subpath: string;
+  skillName: string;
+  name: string;
+  description?: string;
+}
+
+export interface RepoDiscovery {
+  discoveryId: string;
+  repoUrl: string;
+  commitHash: string;
+  skills: DiscoveredSkill[];
 }
 
 export interface SkillManifest {
@@ -56,8 +75,15 @@ export interface InstallResult {
   blocked: boolean;
   securityReport?: SecurityReport;
   skillMetadata?: SkillMetadata;
+  errorKind?: 'auth' | 'cancelled';
 }
 
+// 私有仓库认证方式，只保存密钥路径或环境变量名
+export type GitAuth =
+  | { mode: 'credentialHelper' }
+  | { mode: 'sshKey'; keyPath: string }
+  | { mode: 'tokenEnv'; envVar: string; username?: string };
+
 // 安装时保存的元数据
 export interface SkillMetadata {
   name: string;